@@ -0,0 +1,44 @@
+//! Example showcasing blocking work offloaded onto the thread pool, resolving
+//! names concurrently while a timer keeps the reactor busy.
+use std::{
+    cell::RefCell,
+    io::Result,
+    net::{SocketAddr, ToSocketAddrs as _},
+    rc::Rc,
+    thread,
+    time::Duration,
+};
+
+use uring_playground::{blocking, executor::Executor, reactor::Reactor};
+
+fn resolve(name: &'static str) -> Result<Vec<SocketAddr>> {
+    Ok(name.to_socket_addrs()?.collect())
+}
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let executor = Executor::new(reactor)?;
+
+    let lookups = ["localhost:80", "127.0.0.1:443"].map(|name| {
+        executor.spawn(async move { (name, blocking::spawn_blocking(move || resolve(name)).await) })
+    });
+
+    let slow = executor.spawn(blocking::spawn_blocking(|| {
+        thread::sleep(Duration::from_millis(100));
+        thread::current().name().map(String::from)
+    }));
+
+    executor.run_until(async {
+        for lookup in lookups {
+            if let Some((name, result)) = lookup.await {
+                println!("{name} resolved to {:?}", result??);
+            }
+        }
+
+        if let Some(result) = slow.await {
+            println!("slow work finished on {:?}", result?);
+        }
+
+        Ok(())
+    })?
+}
@@ -0,0 +1,33 @@
+//! Example showcasing how the completion based adapter keeps a read in flight
+//! even after polling for it has been abandoned.
+use std::{cell::RefCell, future::poll_fn, io::Result, rc::Rc, task::Poll};
+
+use uring_playground::{adapter::CompletionBacked, reactor::Reactor};
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let (reader, writer) = std::io::pipe()?;
+    let mut reader = CompletionBacked::new(Rc::clone(&reactor), reader);
+    let mut writer = CompletionBacked::new(Rc::clone(&reactor), writer);
+
+    uring_playground::block_on(&reactor, async {
+        let mut buffer = [0; 64];
+
+        // start a read and give up on it right away, nothing has been written yet
+        poll_fn(|context| {
+            assert!(reader.poll_read(context, &mut buffer).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+
+        poll_fn(|context| writer.poll_write(context, b"hello through a pipe")).await?;
+        poll_fn(|context| writer.poll_flush(context)).await?;
+
+        // the abandoned read completes with the data instead of losing it
+        let amount = poll_fn(|context| reader.poll_read(context, &mut buffer)).await?;
+        assert_eq!(&buffer[..amount], b"hello through a pipe");
+
+        println!("read {amount} bytes through the abandoned read");
+        Ok(())
+    })?
+}
@@ -0,0 +1,27 @@
+//! Example showcasing how many datagrams can be sent and received with a
+//! single round trip through the ring.
+use std::{cell::RefCell, io::Result, rc::Rc};
+
+use uring_playground::{net::UdpSocket, reactor::Reactor};
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let socket = UdpSocket::bind(Rc::clone(&reactor), "127.0.0.1:0")?;
+    let address = socket.local_addr()?;
+
+    uring_playground::block_on(&reactor, async {
+        let datagrams = (0..8).map(|index| (format!("datagram {index}").into_bytes(), address));
+        for sent in socket.send_to_batch(datagrams).await {
+            sent?;
+        }
+
+        // more buffers than datagrams, as it only fills as many as have arrived
+        let buffers = (0..16).map(|_| Vec::with_capacity(64));
+        for received in socket.recv_from_batch(buffers).await {
+            let (buffer, sender) = received?;
+            println!("{sender} sent {:?}", String::from_utf8_lossy(&buffer));
+        }
+
+        Ok(())
+    })?
+}
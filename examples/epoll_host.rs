@@ -0,0 +1,73 @@
+//! Example showcasing the reactor embedded inside a plain `epoll` loop, which
+//! also watches a descriptor of its own.
+use std::{
+    cell::RefCell,
+    future::Future as _,
+    io::{Read as _, Result, Write as _},
+    os::fd::AsFd as _,
+    pin::pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+use uring_playground::{
+    embed::Epoll,
+    operation::{Batch as _, Oneshot as _, Read},
+    reactor::Reactor,
+};
+
+const HOST_TOKEN: u64 = 1;
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let epoll = Epoll::new(Rc::clone(&reactor))?;
+
+    let (mut host_reader, mut host_writer) = std::io::pipe()?;
+    let (uring_reader, mut uring_writer) = std::io::pipe()?;
+    epoll.add(
+        host_reader.as_fd(),
+        libc::EPOLLIN.cast_unsigned(),
+        HOST_TOKEN,
+    )?;
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        host_writer.write_all(b"for the host loop")?;
+        thread::sleep(Duration::from_millis(50));
+        uring_writer.write_all(b"for the reactor")
+    });
+
+    let mut read = pin!(Read::new(uring_reader.as_fd(), Vec::with_capacity(64))
+        .into_batch()
+        .build_submission(&reactor));
+
+    let mut context = Context::from_waker(Waker::noop());
+    let mut ready = Vec::new();
+
+    loop {
+        if let Poll::Ready(buffer) = read.as_mut().poll(&mut context) {
+            println!("reactor read {:?}", String::from_utf8_lossy(&buffer?));
+            break;
+        }
+
+        epoll.wait(Some(Duration::from_secs(1)), &mut ready)?;
+
+        for token in std::mem::take(&mut ready) {
+            assert_eq!(token, HOST_TOKEN);
+
+            let mut buffer = [0; 64];
+            let amount = host_reader.read(&mut buffer)?;
+
+            if amount == 0 {
+                epoll.remove(host_reader.as_fd())?;
+                continue;
+            }
+
+            println!("host read {:?}", String::from_utf8_lossy(&buffer[..amount]));
+        }
+    }
+
+    sender.join().expect("sender thread panicked")
+}
@@ -0,0 +1,66 @@
+//! Example showcasing a foreign event loop driving the reactor, only ever
+//! sleeping in `poll` on the `eventfd` registered for completions.
+use std::{
+    cell::RefCell,
+    future::Future as _,
+    io::{Error, Result, Write as _},
+    os::fd::{AsFd as _, AsRawFd as _},
+    pin::pin,
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+use uring_playground::{
+    operation::{Batch as _, Oneshot as _, Read},
+    reactor::Reactor,
+};
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new)?;
+    let eventfd = reactor.borrow_mut().register_eventfd()?.as_raw_fd();
+
+    let (reader, mut writer) = std::io::pipe()?;
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        writer.write_all(b"hello from another thread")
+    });
+
+    let mut read = pin!(Read::new(reader.as_fd(), Vec::with_capacity(64))
+        .into_batch()
+        .build_submission(&reactor));
+    let mut context = Context::from_waker(Waker::noop());
+    let mut sleeps = 0;
+
+    let buffer = loop {
+        if let Poll::Ready(result) = read.as_mut().poll(&mut context) {
+            break result?;
+        }
+
+        // only sleep once there's nothing left to handle
+        if reactor.borrow_mut().process_completions()? > 0 {
+            continue;
+        }
+
+        let mut descriptor = libc::pollfd {
+            fd: eventfd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        // SAFETY: the descriptor is valid for the duration of the call
+        if unsafe { libc::poll(&raw mut descriptor, 1, 1000) } < 0 {
+            return Err(Error::last_os_error());
+        }
+
+        sleeps += 1;
+    };
+
+    sender.join().expect("sender thread panicked")?;
+    println!(
+        "read {:?} after sleeping {sleeps} times",
+        String::from_utf8_lossy(&buffer)
+    );
+
+    Ok(())
+}
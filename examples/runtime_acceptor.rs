@@ -0,0 +1,71 @@
+//! Example showcasing the multithreaded runtime by accepting connections on
+//! one worker and sending them over to the others through their rings.
+use std::{
+    io::{Read as _, Result, Write as _},
+    net::{self, Shutdown},
+    os::fd::{AsFd as _, OwnedFd},
+    thread,
+};
+
+use uring_playground::{
+    net::TcpListener,
+    operation::{Batch as _, Oneshot as _, Recv, Send},
+    reactor,
+    runtime::Runtime,
+};
+
+const CONNECTIONS: usize = 4;
+
+async fn echo(socket: OwnedFd) -> Result<()> {
+    let buffer = Recv::new(socket.as_fd(), Vec::with_capacity(512))
+        .into_batch()
+        .submit()
+        .await?;
+
+    Send::new(socket.as_fd(), buffer)
+        .into_batch()
+        .submit()
+        .await?;
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let runtime = Runtime::new(3, 64)?;
+    let handle = runtime.handle().clone();
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    runtime.handle().spawn_on(0, move || async move {
+        let listener = TcpListener::bind(reactor::current(), "127.0.0.1:0")?;
+        _ = sender.send(listener.local_addr()?);
+
+        for index in 0..CONNECTIONS {
+            let (stream, peer) = listener.accept().await?;
+            let socket = stream.as_fd().try_clone_to_owned()?;
+            let core = 1 + index % (handle.cores() - 1);
+
+            println!("sending connection from {peer} over to worker {core}");
+            handle.send_fd(core, socket, |socket| async move {
+                let name = thread::current().name().map(str::to_owned);
+                echo(socket).await?;
+                println!("echoed on {}", name.unwrap_or_default());
+                Ok::<_, std::io::Error>(())
+            })?;
+        }
+
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    let address = receiver.recv().expect("acceptor should send the address");
+
+    for index in 0..CONNECTIONS {
+        let mut stream = net::TcpStream::connect(address)?;
+        stream.write_all(format!("hello number {index}").as_bytes())?;
+        stream.shutdown(Shutdown::Write)?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        assert_eq!(response, format!("hello number {index}"));
+    }
+
+    runtime.shutdown()
+}
@@ -0,0 +1,61 @@
+//! Example showcasing the executor by spawning a task for every connection
+//! accepted by an echo server.
+use std::{cell::RefCell, io::Result, net::Shutdown, rc::Rc};
+
+use uring_playground::{
+    executor::{self, Executor},
+    net::{TcpListener, TcpStream},
+    reactor::Reactor,
+};
+
+async fn handle(stream: TcpStream) -> Result<usize> {
+    let buffer = stream.read(Vec::with_capacity(512)).await?;
+    let (amount, _) = stream.write(buffer).await?;
+    stream.shutdown(Shutdown::Write).await?;
+
+    Ok(amount)
+}
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let executor = Executor::new(Rc::clone(&reactor))?;
+
+    let listener = TcpListener::bind(Rc::clone(&reactor), "127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+
+    let server = executor.spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            // detached as nobody cares about the outcome
+            _ = executor::spawn_local(handle(stream));
+        }
+
+        #[expect(unreachable_code)]
+        Ok::<_, std::io::Error>(())
+    });
+
+    executor.run_until(async {
+        let mut clients = Vec::new();
+        for index in 0..3 {
+            let reactor = Rc::clone(&reactor);
+
+            clients.push(executor::spawn_local(async move {
+                let stream = TcpStream::connect(reactor, address).await?;
+                let message = format!("hello from client {index}");
+                stream.write(message.into_bytes()).await?;
+
+                let buffer = stream.read(Vec::with_capacity(512)).await?;
+                Ok::<_, std::io::Error>(String::from_utf8_lossy(&buffer).into_owned())
+            }));
+        }
+
+        for client in clients {
+            println!("{}", client.await.expect("client wasn't aborted")?);
+        }
+
+        server.abort();
+        assert!(server.await.is_none());
+
+        Ok(())
+    })?
+}
@@ -0,0 +1,37 @@
+//! Example showcasing the high-level TCP types by echoing a message back over
+//! a loopback connection.
+use std::{cell::RefCell, io::Result, net::Shutdown, rc::Rc};
+
+use uring_playground::{
+    net::{TcpListener, TcpStream},
+    reactor::Reactor,
+};
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let listener = TcpListener::bind(Rc::clone(&reactor), "127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+
+    uring_playground::block_on(&reactor, async {
+        let server = async {
+            let (stream, peer) = listener.accept().await?;
+            let buffer = stream.read(Vec::with_capacity(512)).await?;
+            println!("echoing {} bytes back to {peer}", buffer.len());
+
+            stream.write(buffer).await?;
+            stream.shutdown(Shutdown::Write).await
+        };
+
+        let client = async {
+            let stream = TcpStream::connect(Rc::clone(&reactor), address).await?;
+            stream.write(b"hello over tcp".to_vec()).await?;
+
+            let buffer = stream.read(Vec::with_capacity(512)).await?;
+            assert_eq!(buffer, b"hello over tcp");
+            Ok(())
+        };
+
+        let (server, client) = futures_lite::future::zip(server, client).await;
+        server.and(client)
+    })?
+}
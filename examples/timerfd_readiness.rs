@@ -0,0 +1,74 @@
+//! Example showcasing waiting for arbitrary file readiness by waiting for a
+//! `timerfd` to expire.
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{Error, Read as _, Result},
+    os::fd::FromRawFd as _,
+    rc::Rc,
+    time::Instant,
+};
+
+use uring_playground::{
+    adapter::{AsyncFd, Interest},
+    reactor::Reactor,
+};
+
+fn main() -> Result<()> {
+    // SAFETY: plain system call without any pointers
+    let descriptor = unsafe {
+        libc::timerfd_create(
+            libc::CLOCK_MONOTONIC,
+            libc::TFD_NONBLOCK | libc::TFD_CLOEXEC,
+        )
+    };
+
+    if descriptor < 0 {
+        return Err(Error::last_os_error());
+    }
+
+    // SAFETY: we just created the descriptor and nothing else owns it
+    let timer = unsafe { File::from_raw_fd(descriptor) };
+
+    let expiration = libc::itimerspec {
+        it_interval: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        },
+        it_value: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 50_000_000,
+        },
+    };
+
+    // SAFETY: the specification is valid for the duration of the call
+    if unsafe { libc::timerfd_settime(descriptor, 0, &raw const expiration, std::ptr::null_mut()) }
+        < 0
+    {
+        return Err(Error::last_os_error());
+    }
+
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let timer = AsyncFd::new(Rc::clone(&reactor), timer);
+    let start = Instant::now();
+
+    let expirations = uring_playground::block_on(&reactor, async {
+        loop {
+            let guard = timer.readable().await?;
+            assert!(guard.ready().contains(Interest::READABLE));
+
+            let mut buffer = [0; 8];
+            match guard.try_io(|mut timer| timer.read_exact(&mut buffer)) {
+                Ok(()) => return Ok::<_, Error>(u64::from_ne_bytes(buffer)),
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => (),
+                Err(error) => return Err(error),
+            }
+        }
+    })??;
+
+    println!(
+        "timer expired {expirations} time(s) after {:?}",
+        start.elapsed()
+    );
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Example showcasing the reactor being driven from within a `tokio` runtime,
+//! with operations of both running side by side.
+use std::{
+    cell::RefCell,
+    io::{Result, Write as _},
+    os::fd::AsFd as _,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use tokio::task::LocalSet;
+use uring_playground::{
+    embed,
+    operation::{Batch as _, Oneshot as _, Read},
+    reactor::Reactor,
+};
+
+fn main() -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let (reader, mut writer) = std::io::pipe()?;
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        writer.write_all(b"hello from another thread")
+    });
+
+    LocalSet::new().block_on(&runtime, async {
+        let driver = tokio::task::spawn_local(embed::drive_on_tokio(Rc::clone(&reactor)));
+        let start = Instant::now();
+
+        // the driver is idle by now, so queueing the read has to wake it up
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let (buffer, ()) = futures_lite::future::zip(
+            Read::new(reader.as_fd(), Vec::with_capacity(64))
+                .into_batch()
+                .build_submission(&reactor),
+            tokio::time::sleep(Duration::from_millis(50)),
+        )
+        .await;
+
+        println!(
+            "read {:?} after {:?}",
+            String::from_utf8_lossy(&buffer?),
+            start.elapsed()
+        );
+
+        driver.abort();
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    sender.join().expect("sender thread panicked")
+}
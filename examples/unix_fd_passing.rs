@@ -0,0 +1,51 @@
+//! Example showcasing unix stream sockets on the abstract namespace and passing
+//! descriptors between them.
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{Read as _, Result, Write as _},
+    os::{fd::AsFd as _, linux::net::SocketAddrExt as _, unix::net::SocketAddr},
+    rc::Rc,
+};
+
+use uring_playground::{
+    net::{UnixListener, UnixStream},
+    reactor::Reactor,
+};
+
+fn main() -> Result<()> {
+    let reactor = Reactor::new(64).map(RefCell::new).map(Rc::new)?;
+    let address = SocketAddr::from_abstract_name(b"uring-playground-example")?;
+    let listener = UnixListener::bind_addr(Rc::clone(&reactor), &address)?;
+
+    let (reader, mut writer) = std::io::pipe()?;
+    writer.write_all(b"hello through a passed pipe")?;
+    drop(writer);
+
+    let received = uring_playground::block_on(&reactor, async {
+        let server = async {
+            let (stream, _) = listener.accept().await?;
+            let credentials = stream.peer_credentials()?;
+            println!("accepted connection from process {}", credentials.process());
+
+            stream.send_fds(b"!".to_vec(), &[reader.as_fd()]).await
+        };
+
+        let client = async {
+            let stream = UnixStream::connect_addr(Rc::clone(&reactor), &address).await?;
+            stream.recv_fds(Vec::with_capacity(1), 1).await
+        };
+
+        let (server, client) = futures_lite::future::zip(server, client).await;
+        server.and(client)
+    })??;
+
+    let (_, descriptors) = received;
+    for descriptor in descriptors {
+        let mut contents = String::new();
+        File::from(descriptor).read_to_string(&mut contents)?;
+        println!("read {contents:?} from the received descriptor");
+    }
+
+    Ok(())
+}
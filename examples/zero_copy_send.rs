@@ -0,0 +1,35 @@
+//! Example showcasing how zero-copy sends only hand the buffer back after the
+//! kernel is done with it.
+use std::{
+    cell::RefCell,
+    io::{Read as _, Result},
+    net::{TcpListener, TcpStream},
+    os::fd::AsFd as _,
+};
+
+use uring_playground::{
+    operation::{Batch as _, SendZc},
+    reactor::Reactor,
+};
+
+fn main() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let sender = TcpStream::connect(listener.local_addr()?)?;
+    let (mut receiver, _) = listener.accept()?;
+
+    let reactor = Reactor::new(64).map(RefCell::new)?;
+    let (sent, buffer) = uring_playground::block_on(&reactor, async {
+        SendZc::new(sender.as_fd(), b"hello from the ring".to_vec())
+            .build_submission(&reactor)
+            .await
+    })?;
+
+    let amount = sent?;
+
+    let mut contents = vec![0; amount];
+    receiver.read_exact(&mut contents)?;
+    assert_eq!(contents, buffer);
+
+    println!("sent {amount} bytes and got the buffer back");
+    Ok(())
+}
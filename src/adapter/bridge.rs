@@ -0,0 +1,298 @@
+use std::{
+    cell::RefCell,
+    io::{Error, ErrorKind, Result},
+    os::fd::{AsRawFd as _, BorrowedFd},
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use io_uring::{
+    opcode::{Read, Recv, Send, Shutdown, Write},
+    squeue::Entry,
+    types::Fd,
+};
+
+use crate::reactor::{OperationId, Reactor};
+
+/// Which operations to use for the actual IO.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Generic reads and writes at the current file position.
+    File,
+    /// Socket specific operations that avoid raising `SIGPIPE`.
+    Socket,
+}
+
+impl Kind {
+    /// Build a read entry.
+    fn read_entry(self, descriptor: Fd, buffer: *mut u8, length: u32) -> Entry {
+        match self {
+            // offset of -1 means the current file position
+            Self::File => Read::new(descriptor, buffer, length)
+                .offset(u64::MAX)
+                .build(),
+            Self::Socket => Recv::new(descriptor, buffer, length).build(),
+        }
+    }
+
+    /// Build a write entry.
+    fn write_entry(self, descriptor: Fd, buffer: *const u8, length: u32) -> Entry {
+        match self {
+            Self::File => Write::new(descriptor, buffer, length)
+                .offset(u64::MAX)
+                .build(),
+            Self::Socket => Send::new(descriptor, buffer, length)
+                .flags(libc::MSG_NOSIGNAL)
+                .build(),
+        }
+    }
+}
+
+/// Bridge between poll based IO and completion based operations that need
+/// owned buffers, by copying data out of and into buffers that are kept in
+/// flight internally.
+#[must_use]
+pub struct Bridge {
+    consumed: usize,
+    kind: Kind,
+    reactor: Rc<RefCell<Reactor>>,
+    read: Option<OperationId>,
+    readable: Vec<u8>,
+    shutdown: Option<OperationId>,
+    writable: Vec<u8>,
+    write: Option<OperationId>,
+    written: usize,
+}
+
+impl Bridge {
+    /// Minimum capacity of the internal read buffer.
+    const READ_CAPACITY: usize = 8 * 1024;
+
+    /// Create a bridge for any kind of file descriptor.
+    pub const fn file(reactor: Rc<RefCell<Reactor>>) -> Self {
+        Self::new(reactor, Kind::File)
+    }
+
+    const fn new(reactor: Rc<RefCell<Reactor>>, kind: Kind) -> Self {
+        Self {
+            reactor,
+            kind,
+            read: None,
+            readable: Vec::new(),
+            consumed: 0,
+            write: None,
+            writable: Vec::new(),
+            written: 0,
+            shutdown: None,
+        }
+    }
+
+    /// Poll for every accepted write to have been fully sent.
+    pub fn poll_flush(&mut self, descriptor: BorrowedFd, context: &Context) -> Poll<Result<()>> {
+        while let Some(operation) = self.write {
+            let output = self
+                .reactor
+                .borrow_mut()
+                .poll_completion(operation, context);
+
+            let result = std::task::ready!(output);
+            self.write = None;
+
+            let entry = match result {
+                Ok(entry) => entry,
+                Err(error) => {
+                    self.writable.clear();
+                    return Poll::Ready(Err(error));
+                }
+            };
+
+            if entry.result().is_negative() {
+                self.writable.clear();
+                return Poll::Ready(Err(Error::from_raw_os_error(-entry.result())));
+            }
+
+            if entry.result() == 0 {
+                self.writable.clear();
+                return Poll::Ready(Err(Error::from(ErrorKind::WriteZero)));
+            }
+
+            self.written += usize::try_from(entry.result()).unwrap_or(usize::MAX);
+            if self.written < self.writable.len() {
+                self.submit_write(descriptor, context);
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Poll for data to be read into the specified buffer.
+    pub fn poll_read(
+        &mut self,
+        descriptor: BorrowedFd,
+        context: &Context,
+        buffer: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if buffer.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            let remaining = &self.readable[self.consumed..];
+            if !remaining.is_empty() {
+                let amount = remaining.len().min(buffer.len());
+                buffer[..amount].copy_from_slice(&remaining[..amount]);
+                self.consumed += amount;
+
+                return Poll::Ready(Ok(amount));
+            }
+
+            let operation = *self.read.get_or_insert_with(|| {
+                self.readable.clear();
+                self.readable.reserve(buffer.len().max(Self::READ_CAPACITY));
+                self.consumed = 0;
+
+                let spare = self.readable.spare_capacity_mut();
+                let entry = self.kind.read_entry(
+                    Fd(descriptor.as_raw_fd()),
+                    spare.as_mut_ptr().cast(),
+                    spare.len().try_into().unwrap_or(u32::MAX),
+                );
+
+                // SAFETY: the buffer is kept alive through the drop implementation
+                unsafe {
+                    self.reactor
+                        .borrow_mut()
+                        .queue_submission(entry, Some(context))
+                }
+            });
+
+            let output = self
+                .reactor
+                .borrow_mut()
+                .poll_completion(operation, context);
+
+            let result = std::task::ready!(output);
+            self.read = None;
+
+            let entry = result?;
+
+            if entry.result().is_negative() {
+                return Poll::Ready(Err(Error::from_raw_os_error(-entry.result())));
+            }
+
+            if entry.result() == 0 {
+                return Poll::Ready(Ok(0));
+            }
+
+            // SAFETY: we have to trust the kernel
+            unsafe {
+                let amount = entry.result().try_into().unwrap_or(usize::MAX);
+                self.readable.set_len(amount);
+            }
+        }
+    }
+
+    /// Poll for everything to be flushed and for sockets to also have their
+    /// write half shut down.
+    pub fn poll_shutdown(&mut self, descriptor: BorrowedFd, context: &Context) -> Poll<Result<()>> {
+        std::task::ready!(self.poll_flush(descriptor, context))?;
+
+        if self.kind == Kind::File {
+            return Poll::Ready(Ok(()));
+        }
+
+        let operation = *self.shutdown.get_or_insert_with(|| {
+            let entry = Shutdown::new(Fd(descriptor.as_raw_fd()), libc::SHUT_WR).build();
+
+            // SAFETY: nothing to invalidate
+            unsafe {
+                self.reactor
+                    .borrow_mut()
+                    .queue_submission(entry, Some(context))
+            }
+        });
+
+        let output = self
+            .reactor
+            .borrow_mut()
+            .poll_completion(operation, context);
+
+        let result = std::task::ready!(output);
+        self.shutdown = None;
+
+        let entry = result?;
+
+        if entry.result().is_negative() {
+            return Poll::Ready(Err(Error::from_raw_os_error(-entry.result())));
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Poll for data to be accepted for writing, which copies it internally
+    /// and only waits for any previous write to finish.
+    pub fn poll_write(
+        &mut self,
+        descriptor: BorrowedFd,
+        context: &Context,
+        buffer: &[u8],
+    ) -> Poll<Result<usize>> {
+        // a zero length write would look like the descriptor refusing data
+        if buffer.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        std::task::ready!(self.poll_flush(descriptor, context))?;
+
+        self.writable.clear();
+        self.writable.extend_from_slice(buffer);
+        self.written = 0;
+        self.submit_write(descriptor, context);
+
+        Poll::Ready(Ok(buffer.len()))
+    }
+
+    /// Create a bridge for a connected stream socket.
+    pub const fn socket(reactor: Rc<RefCell<Reactor>>) -> Self {
+        Self::new(reactor, Kind::Socket)
+    }
+
+    /// Submit whatever hasn't been written yet.
+    fn submit_write(&mut self, descriptor: BorrowedFd, context: &Context) {
+        let remaining = &self.writable[self.written..];
+        let entry = self.kind.write_entry(
+            Fd(descriptor.as_raw_fd()),
+            remaining.as_ptr(),
+            remaining.len().try_into().unwrap_or(u32::MAX),
+        );
+
+        // SAFETY: the buffer is kept alive through the drop implementation
+        let operation = unsafe {
+            self.reactor
+                .borrow_mut()
+                .queue_submission(entry, Some(context))
+        };
+
+        self.write = Some(operation);
+    }
+}
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        let mut reactor = self.reactor.borrow_mut();
+
+        if let Some(operation) = self.read.take() {
+            let buffer = std::mem::take(&mut self.readable);
+            reactor.ignore_operation(operation, Some(Box::new(buffer)));
+        }
+
+        if let Some(operation) = self.write.take() {
+            let buffer = std::mem::take(&mut self.writable);
+            reactor.ignore_operation(operation, Some(Box::new(buffer)));
+        }
+
+        if let Some(operation) = self.shutdown.take() {
+            reactor.ignore_operation(operation, None);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(feature = "futures-io")]
+mod futures_io_impls {
+    use std::{
+        io::Result,
+        os::fd::AsFd,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures_io::{AsyncRead, AsyncWrite};
+
+    use crate::adapter::CompletionBacked;
+
+    impl<T: AsFd + Unpin> AsyncRead for CompletionBacked<T> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize>> {
+            self.get_mut().poll_read(cx, buf)
+        }
+    }
+
+    impl<T: AsFd + Unpin> AsyncWrite for CompletionBacked<T> {
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.get_mut().poll_close(cx)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.get_mut().poll_flush(cx)
+        }
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+    }
+}
+
+#[cfg(feature = "tokio-compat")]
+mod tokio_impls {
+    use std::{
+        io::Result,
+        os::fd::AsFd,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    use crate::adapter::CompletionBacked;
+
+    impl<T: AsFd + Unpin> AsyncRead for CompletionBacked<T> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<Result<()>> {
+            let amount =
+                std::task::ready!(self.get_mut().poll_read(cx, buf.initialize_unfilled()))?;
+            buf.advance(amount);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl<T: AsFd + Unpin> AsyncWrite for CompletionBacked<T> {
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.get_mut().poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.get_mut().poll_close(cx)
+        }
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+    }
+}
+
+use std::{
+    cell::RefCell,
+    io::Result,
+    os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use crate::{adapter::Bridge, reactor::Reactor};
+
+/// Adapter for doing IO operations through completion based reads and writes
+/// with buffers kept in flight internally, which avoids the extra round trip of
+/// first polling for readiness.
+///
+/// Data is copied out of and into the internal buffers, so an ongoing read
+/// survives polling being stopped midway without losing anything.
+#[must_use]
+pub struct CompletionBacked<T> {
+    bridge: Bridge,
+    inner: T,
+}
+
+// the trait implementations just forward to these inherent methods
+#[cfg_attr(
+    any(feature = "futures-io", feature = "tokio-compat"),
+    expect(clippy::same_name_method)
+)]
+impl<T: AsFd> CompletionBacked<T> {
+    /// Get a shared reference to the wrapped value.
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub const fn new(reactor: Rc<RefCell<Reactor>>, inner: T) -> Self {
+        Self {
+            inner,
+            bridge: Bridge::file(reactor),
+        }
+    }
+
+    /// Poll for all previously written data to be written out before closing,
+    /// which doesn't actually close anything as that happens on drop.
+    pub fn poll_close(&mut self, context: &Context) -> Poll<Result<()>> {
+        self.poll_flush(context)
+    }
+
+    /// Poll for all previously written data to be written out.
+    pub fn poll_flush(&mut self, context: &Context) -> Poll<Result<()>> {
+        self.bridge.poll_flush(self.inner.as_fd(), context)
+    }
+
+    /// Poll for data to be read into the specified buffer.
+    pub fn poll_read(&mut self, context: &Context, buffer: &mut [u8]) -> Poll<Result<usize>> {
+        self.bridge.poll_read(self.inner.as_fd(), context, buffer)
+    }
+
+    /// Poll for data to be written from the specified buffer.
+    ///
+    /// The data is copied into an internal buffer, so this only waits for any
+    /// previous write to finish.
+    pub fn poll_write(&mut self, context: &Context, buffer: &[u8]) -> Poll<Result<usize>> {
+        self.bridge.poll_write(self.inner.as_fd(), context, buffer)
+    }
+}
+
+impl<T: AsFd> AsFd for CompletionBacked<T> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl<T: AsRawFd> AsRawFd for CompletionBacked<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
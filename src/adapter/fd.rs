@@ -0,0 +1,212 @@
+// submodule for poll flags to allow the cast from libc's weird type choice
+#[expect(clippy::as_conversions)]
+mod poll {
+    pub const READABLE: u32 = libc::POLLIN as u32;
+    pub const WRITABLE: u32 = libc::POLLOUT as u32;
+    pub const PRIORITY: u32 = libc::POLLPRI as u32;
+    pub const READ_CLOSED: u32 = libc::POLLRDHUP as u32;
+    pub const ERROR: u32 = libc::POLLERR as u32;
+    pub const HANGUP: u32 = libc::POLLHUP as u32;
+}
+
+use std::{
+    cell::{Cell, RefCell},
+    io::{ErrorKind, Result},
+    ops::{BitOr, BitOrAssign},
+    os::fd::{AsRawFd, BorrowedFd, RawFd},
+    rc::Rc,
+};
+
+use crate::{
+    operation::{Batch as _, Oneshot as _, PollAdd},
+    reactor::Reactor,
+};
+
+/// Set of readiness events to wait for or that have been reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct Interest(u32);
+
+impl Interest {
+    /// An error condition, which is always reported regardless of interest.
+    pub const ERROR: Self = Self(poll::ERROR);
+    /// The file was hung up on, which is always reported regardless of
+    /// interest.
+    pub const HANGUP: Self = Self(poll::HANGUP);
+    /// Exceptional condition, such as out-of-band data.
+    pub const PRIORITY: Self = Self(poll::PRIORITY);
+    /// Data is available for reading.
+    pub const READABLE: Self = Self(poll::READABLE);
+    /// The peer closed its writing half of the connection.
+    pub const READ_CLOSED: Self = Self(poll::READ_CLOSED);
+    /// Data can be written without blocking.
+    pub const WRITABLE: Self = Self(poll::WRITABLE);
+
+    /// Check whether all of the specified events are included.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Check whether any of the specified events are included.
+    #[must_use]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Check whether no events are included.
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Interest {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Interest {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Wrapper for waiting on arbitrary readiness events of a file, for things
+/// that don't fit the shape of reading and writing bytes.
+///
+/// Reported readiness is cached until explicitly cleared through the returned
+/// [`ReadyGuard`], which is expected to be done after the file reports that
+/// the operation would block, so the file should be in non-blocking mode.
+#[must_use]
+pub struct AsyncFd<T: AsRawFd> {
+    inner: T,
+    reactor: Rc<RefCell<Reactor>>,
+    readiness: Cell<u32>,
+}
+
+impl<T: AsRawFd> AsyncFd<T> {
+    /// Get a mutable reference to the wrapped value.
+    pub const fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Get a shared reference to the wrapped value.
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the inner value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    pub const fn new(reactor: Rc<RefCell<Reactor>>, inner: T) -> Self {
+        Self {
+            reactor,
+            inner,
+            readiness: Cell::new(0),
+        }
+    }
+
+    /// Wait for the file to become readable.
+    ///
+    /// # Errors
+    ///
+    /// If polling for readiness fails.
+    pub async fn readable(&self) -> Result<ReadyGuard<'_, T>> {
+        self.ready(Interest::READABLE).await
+    }
+
+    /// Wait for the file to become ready for any of the specified events,
+    /// or for an error or hangup to be reported.
+    ///
+    /// # Errors
+    ///
+    /// If polling for readiness fails.
+    pub async fn ready(&self, interest: Interest) -> Result<ReadyGuard<'_, T>> {
+        let wanted = interest | Interest::ERROR | Interest::HANGUP;
+
+        loop {
+            let ready = Interest(self.readiness.get() & wanted.0);
+            if !ready.is_empty() {
+                return Ok(ReadyGuard { fd: self, ready });
+            }
+
+            // SAFETY: the inner value keeps the file open for as long as we're
+            // borrowed
+            let file = unsafe { BorrowedFd::borrow_raw(self.inner.as_raw_fd()) };
+
+            let events = PollAdd::new(file, wanted.0)
+                .into_batch()
+                .build_submission(&self.reactor)
+                .await?;
+
+            self.readiness.set(self.readiness.get() | events);
+        }
+    }
+
+    /// Wait for the file to become writable.
+    ///
+    /// # Errors
+    ///
+    /// If polling for readiness fails.
+    pub async fn writable(&self) -> Result<ReadyGuard<'_, T>> {
+        self.ready(Interest::WRITABLE).await
+    }
+}
+
+impl<T: AsRawFd> AsRawFd for AsyncFd<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// Proof of a file having been reported ready, which is cached until cleared.
+#[must_use]
+pub struct ReadyGuard<'fd, T: AsRawFd> {
+    fd: &'fd AsyncFd<T>,
+    ready: Interest,
+}
+
+impl<T: AsRawFd> ReadyGuard<'_, T> {
+    /// Forget the cached readiness, so that waiting again actually polls the
+    /// file.
+    pub fn clear_ready(self) {
+        let readiness = &self.fd.readiness;
+        readiness.set(readiness.get() & !self.ready.0);
+    }
+
+    /// Get a shared reference to the wrapped value.
+    #[must_use]
+    pub const fn get_ref(&self) -> &T {
+        &self.fd.inner
+    }
+
+    /// Events that the file has been reported ready for.
+    pub const fn ready(&self) -> Interest {
+        self.ready
+    }
+
+    /// Attempt an operation, clearing the readiness if it would block.
+    ///
+    /// # Errors
+    ///
+    /// If the operation fails, including with [`ErrorKind::WouldBlock`] after
+    /// the readiness has been cleared.
+    pub fn try_io<R, F: FnOnce(&T) -> Result<R>>(self, operation: F) -> Result<R> {
+        let output = operation(&self.fd.inner);
+
+        if output
+            .as_ref()
+            .is_err_and(|error| error.kind() == ErrorKind::WouldBlock)
+        {
+            self.clear_ready();
+        }
+
+        output
+    }
+}
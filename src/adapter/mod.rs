@@ -0,0 +1,12 @@
+//! Compatibility wrappers for doing poll based IO.
+mod bridge;
+mod completion;
+mod fd;
+mod readiness;
+
+pub(crate) use self::bridge::Bridge;
+pub use self::{
+    completion::CompletionBacked,
+    fd::{AsyncFd, Interest, ReadyGuard},
+    readiness::ReadinessBacked,
+};
@@ -0,0 +1,417 @@
+use std::{
+    cell::RefCell,
+    io::{Error, ErrorKind, Read, Result, Write},
+    net::Shutdown,
+    os::fd::AsRawFd,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use io_uring::{
+    cqueue,
+    opcode::{PollAdd, PollRemove, Shutdown as ShutdownOp},
+    squeue::Flags,
+    types::Fd,
+};
+
+use crate::reactor::{OperationId, Reactor};
+
+// submodule for poll flags to allow the cast from libc's weird type choice
+#[expect(clippy::as_conversions)]
+mod poll {
+    pub const READABLE: u32 = libc::POLLIN as u32;
+    pub const WRITABLE: u32 = libc::POLLOUT as u32;
+    pub const TERMINAL: u32 = (libc::POLLERR | libc::POLLHUP) as u32;
+}
+
+#[cfg(feature = "futures-io")]
+mod futures_io_impls {
+    use std::{
+        io::{Read, Result, Write},
+        os::fd::AsRawFd,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+    use crate::adapter::ReadinessBacked;
+
+    impl<T: AsRawFd + Read + Unpin> AsyncRead for ReadinessBacked<T> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize>> {
+            self.get_mut().poll_read(cx, buf)
+        }
+    }
+
+    impl<T: AsRawFd + Read + Unpin> AsyncBufRead for ReadinessBacked<T> {
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            self.get_mut().consume(amt);
+        }
+
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+            self.get_mut().poll_fill_buf(cx)
+        }
+    }
+
+    impl<T: AsRawFd + Write + Unpin> AsyncWrite for ReadinessBacked<T> {
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.get_mut().poll_shutdown(cx)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+            Poll::Ready(self.get_mut().inner.flush())
+        }
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+    }
+}
+
+#[cfg(feature = "tokio-compat")]
+mod tokio_impls {
+    use std::{
+        io::{Read, Result, Write},
+        os::fd::AsRawFd,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    use crate::adapter::ReadinessBacked;
+
+    impl<T: AsRawFd + Read + Unpin> AsyncRead for ReadinessBacked<T> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<Result<()>> {
+            let amount =
+                std::task::ready!(self.get_mut().poll_read(cx, buf.initialize_unfilled()))?;
+            buf.advance(amount);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl<T: AsRawFd + Write + Unpin> AsyncWrite for ReadinessBacked<T> {
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+            Poll::Ready(self.get_mut().inner.flush())
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.get_mut().poll_shutdown(cx)
+        }
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+    }
+}
+
+/// Adapter for doing IO operations through polling for readiness with
+/// `io_uring` to allow for interfacing with most other libraries.
+///
+/// By default every time an operation would block a new oneshot poll gets
+/// submitted, but [`ReadinessBacked::multishot`] instead keeps a long-lived
+/// edge-triggered poll per direction, whose completions update cached
+/// readiness that's only cleared once the operation would block again.
+///
+/// The event mask of each poll is fixed by its direction, so masks never have
+/// to be changed through `PollUpdate`, and interest in the other direction
+/// gets its own poll instead.
+// TODO: consider using safer operation wrappers
+#[must_use]
+pub struct ReadinessBacked<T> {
+    buffered: Vec<u8>,
+    consumed: usize,
+    inner: T,
+    multishot: bool,
+    reactor: Rc<RefCell<Reactor>>,
+    read: Option<OperationId>,
+    readiness: u32,
+    shutdown: Option<OperationId>,
+    write: Option<OperationId>,
+}
+
+// the trait implementations just forward to these inherent methods
+#[cfg_attr(feature = "futures-io", expect(clippy::same_name_method))]
+impl<T> ReadinessBacked<T> {
+    /// Capacity of the internal buffer used for buffered reading.
+    const BUFFER_CAPACITY: usize = 8 * 1024;
+
+    /// Mark data returned by [`ReadinessBacked::poll_fill_buf`] as consumed.
+    pub fn consume(&mut self, amount: usize) {
+        self.consumed = (self.consumed + amount).min(self.buffered.len());
+    }
+
+    /// Create an adapter that uses multishot polls and cached readiness.
+    pub const fn multishot(reactor: Rc<RefCell<Reactor>>, inner: T) -> Self {
+        Self::with_mode(reactor, inner, true)
+    }
+
+    pub const fn new(reactor: Rc<RefCell<Reactor>>, inner: T) -> Self {
+        Self::with_mode(reactor, inner, false)
+    }
+
+    /// Data that has been read into the internal buffer but not consumed yet.
+    fn remaining(&self) -> &[u8] {
+        &self.buffered[self.consumed..]
+    }
+
+    const fn with_mode(reactor: Rc<RefCell<Reactor>>, inner: T, multishot: bool) -> Self {
+        Self {
+            reactor,
+            inner,
+            read: None,
+            write: None,
+            shutdown: None,
+            buffered: Vec::new(),
+            consumed: 0,
+            multishot,
+            readiness: 0,
+        }
+    }
+}
+
+#[cfg_attr(
+    any(feature = "futures-io", feature = "tokio-compat"),
+    expect(clippy::same_name_method)
+)]
+impl<T: AsRawFd + Read> ReadinessBacked<T> {
+    /// Poll for the internal buffer to contain data, reading more if empty.
+    pub fn poll_fill_buf(&mut self, context: &mut Context) -> Poll<Result<&[u8]>> {
+        if self.remaining().is_empty() {
+            let mut buffered = std::mem::take(&mut self.buffered);
+            buffered.resize(Self::BUFFER_CAPACITY, 0);
+
+            let output = self.poll_read_unbuffered(context, &mut buffered);
+            let amount = match output {
+                Poll::Ready(Ok(amount)) => amount,
+                Poll::Ready(Err(_)) | Poll::Pending => 0,
+            };
+
+            buffered.truncate(amount);
+            self.buffered = buffered;
+            self.consumed = 0;
+
+            std::task::ready!(output)?;
+        }
+
+        Poll::Ready(Ok(self.remaining()))
+    }
+
+    /// Poll for data to be read into the specified buffer.
+    ///
+    /// Any data left over in the internal buffer is handed out first.
+    pub fn poll_read(&mut self, context: &mut Context, buffer: &mut [u8]) -> Poll<Result<usize>> {
+        if !self.remaining().is_empty() {
+            let amount = self.remaining().len().min(buffer.len());
+            buffer[..amount].copy_from_slice(&self.remaining()[..amount]);
+            self.consume(amount);
+
+            return Poll::Ready(Ok(amount));
+        }
+
+        self.poll_read_unbuffered(context, buffer)
+    }
+
+    /// Poll for data to be read directly from the inner value.
+    fn poll_read_unbuffered(
+        &mut self,
+        context: &mut Context,
+        buffer: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        loop {
+            std::task::ready!(self.poll_readiness(context, poll::READABLE))?;
+
+            match self.inner.read(buffer) {
+                Ok(amount) => return Poll::Ready(Ok(amount)),
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    self.clear_readiness(poll::READABLE);
+                }
+                Err(error) => return Poll::Ready(Err(error)),
+            }
+        }
+    }
+}
+
+#[cfg_attr(
+    any(feature = "futures-io", feature = "tokio-compat"),
+    expect(clippy::same_name_method)
+)]
+impl<T: AsRawFd + Write> ReadinessBacked<T> {
+    /// Poll for data to be written from the specified buffer.
+    pub fn poll_write(&mut self, context: &mut Context, buffer: &[u8]) -> Poll<Result<usize>> {
+        loop {
+            std::task::ready!(self.poll_readiness(context, poll::WRITABLE))?;
+
+            match self.inner.write(buffer) {
+                Ok(amount) => return Poll::Ready(Ok(amount)),
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    self.clear_readiness(poll::WRITABLE);
+                }
+                Err(error) => return Poll::Ready(Err(error)),
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "tokio-compat", expect(clippy::same_name_method))]
+impl<T: AsRawFd> ReadinessBacked<T> {
+    /// Forget cached readiness after an operation would have blocked.
+    ///
+    /// The polls are edge-triggered, so the next completion only arrives once
+    /// the descriptor becomes ready again after this.
+    const fn clear_readiness(&mut self, events: u32) {
+        self.readiness &= !(events | poll::TERMINAL);
+    }
+
+    /// Poll for the inner value to be ready for the specified events.
+    fn poll_readiness(&mut self, context: &Context, events: u32) -> Poll<Result<()>> {
+        let slot = if events == poll::READABLE {
+            &mut self.read
+        } else {
+            &mut self.write
+        };
+
+        loop {
+            if self.readiness & (events | poll::TERMINAL) != 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            // a poll per direction rather than a shared one whose mask gets
+            // updated, which would race with completions for the old mask
+            let operation = *slot.get_or_insert_with(|| {
+                let entry = PollAdd::new(Fd(self.inner.as_raw_fd()), events)
+                    .multi(self.multishot)
+                    .build();
+
+                // SAFETY: nothing to invalidate
+                unsafe {
+                    self.reactor
+                        .borrow_mut()
+                        .queue_submission(entry, Some(context))
+                }
+            });
+
+            let output = self
+                .reactor
+                .borrow_mut()
+                .poll_completion(operation, context);
+
+            let entry = match std::task::ready!(output) {
+                Ok(entry) => entry,
+                Err(error) => {
+                    *slot = None;
+                    return Poll::Ready(Err(error));
+                }
+            };
+
+            // multishot polls can still get terminated, for example on overflow
+            if !cqueue::more(entry.flags()) {
+                *slot = None;
+            }
+
+            if entry.result().is_negative() {
+                return Poll::Ready(Err(Error::from_raw_os_error(-entry.result())));
+            }
+
+            // oneshot polls have to be submitted again anyways so there's no
+            // point in remembering anything
+            if !self.multishot {
+                return Poll::Ready(Ok(()));
+            }
+
+            self.readiness |= entry.result().cast_unsigned();
+        }
+    }
+
+    /// Poll for a socket to be gracefully shut down in both directions.
+    pub fn poll_shutdown(&mut self, context: &mut Context) -> Poll<Result<()>> {
+        self.poll_shutdown_with(context, Shutdown::Both)
+    }
+
+    /// Poll for the specified halves of a socket to be gracefully shut down.
+    ///
+    /// Any polls for the affected directions are removed in the same batch of
+    /// linked operations that does the actual shutdown.
+    pub fn poll_shutdown_with(&mut self, context: &Context, how: Shutdown) -> Poll<Result<()>> {
+        let operation = *self.shutdown.get_or_insert_with(|| {
+            let mut reactor = self.reactor.borrow_mut();
+            let (polls, how) = match how {
+                Shutdown::Read => ([self.read.take(), None], libc::SHUT_RD),
+                Shutdown::Write => ([None, self.write.take()], libc::SHUT_WR),
+                Shutdown::Both => ([self.read.take(), self.write.take()], libc::SHUT_RDWR),
+            };
+
+            for id in polls.into_iter().flatten() {
+                // hard links so that polls having already completed don't
+                // prevent the shutdown from happening
+                let entry = PollRemove::new(id.0.to_bits()).build();
+
+                // SAFETY: nothing to invalidate
+                _ = unsafe { reactor.queue_linked_submission(entry, Flags::IO_HARDLINK, None) };
+
+                // the removed poll still posts a final completion
+                reactor.ignore_operation(id, None);
+            }
+
+            let entry = ShutdownOp::new(Fd(self.inner.as_raw_fd()), how).build();
+
+            // SAFETY: drop implementation forgets data
+            unsafe { reactor.queue_submission(entry, Some(context)) }
+        });
+
+        let output = self
+            .reactor
+            .borrow_mut()
+            .poll_completion(operation, context);
+
+        let result = std::task::ready!(output);
+        self.shutdown = None;
+
+        let entry = result?;
+
+        if entry.result().is_negative() {
+            return Poll::Ready(Err(Error::from_raw_os_error(-entry.result())));
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> Drop for ReadinessBacked<T> {
+    fn drop(&mut self) {
+        let mut reactor = self.reactor.borrow_mut();
+
+        // the polls would otherwise stay registered for as long as the file
+        // stays open, which the ring itself ensures
+        for id in [self.read, self.write].into_iter().flatten() {
+            // SAFETY: nothing to invalidate
+            _ = unsafe { reactor.queue_submission(PollRemove::new(id.0.to_bits()).build(), None) };
+        }
+
+        let operations = [self.read.take(), self.write.take(), self.shutdown.take()]
+            .into_iter()
+            .flatten();
+
+        for id in operations {
+            reactor.ignore_operation(id, None);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Pool of threads for running blocking work that has no `io_uring`
+//! counterpart, such as name resolution or heavy computation.
+//!
+//! Results are delivered back with a futex wake, which completes a futex wait
+//! submitted onto the reactor of the waiting task, or wakes it up through its
+//! remote handle on kernels without futex operations.
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io::{Error, Result},
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+        Condvar,
+        Mutex,
+        OnceLock,
+        PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{futex, reactor};
+
+/// Work sent over to the pool.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Output of a closure along with the futex signaling its availability.
+struct Completion<T> {
+    output: Mutex<Option<thread::Result<T>>>,
+    state: AtomicU32,
+}
+
+impl<T> Completion<T> {
+    const STATE_FINISHED: u32 = 1;
+    const STATE_PENDING: u32 = 0;
+
+    fn finish(&self, output: thread::Result<T>) {
+        *self.output.lock().unwrap_or_else(PoisonError::into_inner) = Some(output);
+        self.state.store(Self::STATE_FINISHED, Ordering::Release);
+
+        futex::wake_blocking(&self.state, u32::MAX);
+    }
+}
+
+/// Bookkeeping of the pool, kept behind a single lock.
+struct State {
+    idle: usize,
+    jobs: VecDeque<Job>,
+    threads: usize,
+}
+
+/// Thread pool that grows on demand and shrinks after idling for a while.
+struct Pool {
+    available: Condvar,
+    state: Mutex<State>,
+}
+
+impl Pool {
+    /// How long an idle thread waits for more work before exiting.
+    const KEEP_ALIVE: Duration = Duration::from_secs(10);
+    /// Upper limit for the number of threads, past which jobs get queued.
+    const MAX_THREADS: usize = 64;
+
+    fn execute(&'static self, job: Job) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.jobs.push_back(job);
+
+        if state.jobs.len() > state.idle && state.threads < Self::MAX_THREADS {
+            let spawned = thread::Builder::new()
+                .name(String::from("uring-blocking"))
+                .spawn(|| self.work());
+
+            match spawned {
+                Ok(_) => state.threads += 1,
+                // the job would otherwise never get picked up
+                Err(error) if state.threads == 0 => {
+                    state.jobs.pop_back();
+                    return Err(error);
+                }
+                Err(_) => (),
+            }
+        }
+
+        drop(state);
+        self.available.notify_one();
+
+        Ok(())
+    }
+
+    fn get() -> &'static Self {
+        static POOL: OnceLock<Pool> = OnceLock::new();
+
+        POOL.get_or_init(|| Self {
+            state: Mutex::new(State {
+                jobs: VecDeque::new(),
+                threads: 0,
+                idle: 0,
+            }),
+            available: Condvar::new(),
+        })
+    }
+
+    /// Wait for the next job, or for the thread to idle long enough to exit.
+    fn next_job(&self) -> Option<Job> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        loop {
+            if let Some(job) = state.jobs.pop_front() {
+                drop(state);
+                return Some(job);
+            }
+
+            state.idle += 1;
+            let (guard, timeout) = self
+                .available
+                .wait_timeout(state, Self::KEEP_ALIVE)
+                .unwrap_or_else(PoisonError::into_inner);
+
+            state = guard;
+            state.idle -= 1;
+
+            if timeout.timed_out() && state.jobs.is_empty() {
+                state.threads -= 1;
+                drop(state);
+                return None;
+            }
+        }
+    }
+
+    /// Body of a pool thread.
+    fn work(&self) {
+        while let Some(job) = self.next_job() {
+            job();
+        }
+    }
+}
+
+/// Run a blocking closure on the thread pool, resolving to its output.
+///
+/// The closure is handed off right away rather than when the future is first
+/// polled, and keeps running even if the future gets dropped. Waiting for it
+/// requires the future to be polled with a current reactor.
+///
+/// # Errors
+///
+/// If the pool fails to start a thread to run the closure on, or waiting for
+/// its output fails.
+///
+/// # Panics
+///
+/// If the closure panics, the panic is resumed when the output is awaited.
+/// Awaiting the output also panics if there's no current reactor.
+#[expect(clippy::module_name_repetitions)]
+pub fn spawn_blocking<F, T>(function: F) -> impl Future<Output = Result<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let completion = Arc::new(Completion {
+        state: AtomicU32::new(Completion::<T>::STATE_PENDING),
+        output: Mutex::new(None),
+    });
+
+    let dispatched = Pool::get().execute(Box::new({
+        let completion = Arc::clone(&completion);
+        move || completion.finish(panic::catch_unwind(AssertUnwindSafe(function)))
+    }));
+
+    async move {
+        dispatched?;
+        let reactor = reactor::current();
+
+        while completion.state.load(Ordering::Acquire) == Completion::<T>::STATE_PENDING {
+            futex::wait(&reactor, &completion.state, Completion::<T>::STATE_PENDING).await?;
+        }
+
+        let output = completion
+            .output
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .ok_or_else(|| Error::other("blocking output was already taken"))?;
+
+        Ok(output.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+    }
+}
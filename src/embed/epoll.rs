@@ -0,0 +1,168 @@
+//! Driving the reactor from a plain `epoll` based loop.
+use std::{
+    cell::RefCell,
+    io::{Error, ErrorKind, Result},
+    os::fd::{AsFd, AsRawFd as _, BorrowedFd, FromRawFd as _, OwnedFd},
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::reactor::Reactor;
+
+/// `epoll` instance watching the ring of a reactor alongside descriptors of
+/// the host loop.
+///
+/// Events for the ring are handled internally by driving the reactor, while
+/// the tokens of other ready descriptors are handed back to the host loop.
+/// Descriptors are watched in level triggered mode.
+#[must_use]
+pub struct Epoll {
+    epoll: OwnedFd,
+    reactor: Rc<RefCell<Reactor>>,
+}
+
+impl Epoll {
+    /// Maximum number of events handled per call to [`Epoll::wait`].
+    const EVENTS: usize = 64;
+    /// Token that events for the ring are reported with, which can't be used
+    /// for other descriptors.
+    pub const RING_TOKEN: u64 = u64::MAX;
+
+    /// Watch a descriptor of the host loop for the specified events, such as
+    /// `libc::EPOLLIN`, reporting them with the token.
+    ///
+    /// # Errors
+    ///
+    /// If the token is [`Epoll::RING_TOKEN`] or the underlying system call
+    /// fails.
+    pub fn add(&self, file: BorrowedFd<'_>, events: u32, token: u64) -> Result<()> {
+        if token == Self::RING_TOKEN {
+            return Err(ErrorKind::InvalidInput.into());
+        }
+
+        self.control(libc::EPOLL_CTL_ADD, file, events, token)
+    }
+
+    fn control(&self, operation: i32, file: BorrowedFd<'_>, events: u32, token: u64) -> Result<()> {
+        let mut event = libc::epoll_event { events, u64: token };
+
+        // SAFETY: the event is valid for the duration of the call
+        let result = unsafe {
+            libc::epoll_ctl(
+                self.epoll.as_raw_fd(),
+                operation,
+                file.as_raw_fd(),
+                &raw mut event,
+            )
+        };
+
+        if result < 0 {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Create an `epoll` instance watching the ring of the reactor.
+    ///
+    /// # Errors
+    ///
+    /// If creating the instance or registering the ring fails.
+    pub fn new(reactor: Rc<RefCell<Reactor>>) -> Result<Self> {
+        // SAFETY: plain system call without any pointers
+        let descriptor = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+        if descriptor < 0 {
+            return Err(Error::last_os_error());
+        }
+
+        // SAFETY: we just created the descriptor and nothing else owns it
+        let epoll = unsafe { OwnedFd::from_raw_fd(descriptor) };
+        let this = Self { epoll, reactor };
+
+        this.control(
+            libc::EPOLL_CTL_ADD,
+            this.reactor.borrow().as_fd(),
+            readable(),
+            Self::RING_TOKEN,
+        )?;
+
+        Ok(this)
+    }
+
+    /// Stop watching a descriptor of the host loop.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails, such as when the descriptor isn't
+    /// being watched.
+    pub fn remove(&self, file: BorrowedFd<'_>) -> Result<()> {
+        self.control(libc::EPOLL_CTL_DEL, file, 0, 0)
+    }
+
+    /// Submit queued entries, wait for events until the timeout elapses and
+    /// drive the reactor if the ring became readable.
+    ///
+    /// Tokens of ready host descriptors are appended to `ready`. Futures using
+    /// the reactor should be polled after every call, as entries they queue
+    /// only get submitted on the next one.
+    ///
+    /// # Errors
+    ///
+    /// If driving the reactor or waiting for events fails, with interrupted
+    /// waits being treated as timing out instead.
+    pub fn wait(&self, timeout: Option<Duration>, ready: &mut Vec<u64>) -> Result<()> {
+        self.reactor
+            .borrow_mut()
+            .wait_for_progress(Some(Duration::ZERO))?;
+
+        // rounded up to not spin on timeouts shorter than a millisecond
+        let timeout = timeout.map_or(-1, |timeout| {
+            i32::try_from(timeout.as_nanos().div_ceil(1_000_000)).unwrap_or(i32::MAX)
+        });
+
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; Self::EVENTS];
+
+        // SAFETY: the buffer is valid for the duration of the call
+        let count = unsafe {
+            libc::epoll_wait(
+                self.epoll.as_raw_fd(),
+                events.as_mut_ptr(),
+                i32::try_from(events.len()).unwrap_or(i32::MAX),
+                timeout,
+            )
+        };
+
+        let Ok(count) = usize::try_from(count) else {
+            let error = Error::last_os_error();
+            if error.kind() == ErrorKind::Interrupted {
+                return Ok(());
+            }
+
+            return Err(error);
+        };
+
+        for event in events.iter().take(count) {
+            match event.u64 {
+                Self::RING_TOKEN => self
+                    .reactor
+                    .borrow_mut()
+                    .wait_for_progress(Some(Duration::ZERO))?,
+                token => ready.push(token),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Allows nesting the instance inside yet another event loop.
+impl AsFd for Epoll {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.epoll.as_fd()
+    }
+}
+
+/// Event mask for polling for readability.
+const fn readable() -> u32 {
+    libc::EPOLLIN.cast_unsigned()
+}
@@ -0,0 +1,10 @@
+//! Integrations for embedding the reactor inside the event loops of other
+//! runtimes, which watch the ring for completions and drive the reactor
+//! without ever blocking on it.
+mod epoll;
+#[cfg(feature = "tokio-host")]
+mod tokio;
+
+pub use self::epoll::Epoll;
+#[cfg(feature = "tokio-host")]
+pub use self::tokio::drive_on_tokio;
@@ -0,0 +1,51 @@
+//! Driving the reactor from within a `tokio` runtime.
+use std::{
+    cell::RefCell,
+    convert::Infallible,
+    future::poll_fn,
+    io::Result,
+    os::fd::{AsFd as _, AsRawFd as _},
+    rc::Rc,
+    task::Poll,
+    time::Duration,
+};
+
+use tokio::io::{unix::AsyncFd, Interest};
+
+use crate::reactor::Reactor;
+
+/// Drive the reactor whenever its ring becomes readable or new entries get
+/// queued up, for as long as the returned future is polled.
+///
+/// The reactor isn't `Send`, so this has to be spawned onto a `LocalSet` or
+/// polled on a current thread runtime, next to the tasks using the reactor.
+///
+/// # Errors
+///
+/// If registering the ring with the runtime or driving the reactor fails,
+/// which is the only way for the future to resolve.
+pub async fn drive_on_tokio(reactor: Rc<RefCell<Reactor>>) -> Result<Infallible> {
+    let descriptor = reactor.borrow().as_fd().as_raw_fd();
+
+    // SAFETY: the reactor is dropped only after the registration, so the ring
+    // stays open for its entire lifetime
+    let ring = unsafe { AsyncFd::register_with_interest(descriptor, Interest::READABLE) }?;
+
+    loop {
+        reactor
+            .borrow_mut()
+            .wait_for_progress(Some(Duration::ZERO))?;
+
+        poll_fn(|context| -> Poll<Result<()>> {
+            if let Poll::Ready(mut guard) = ring.poll_read_ready(context)? {
+                // readiness is edge triggered, so it's cleared before reaping
+                // to not miss completions posted in between
+                guard.clear_ready();
+                return Poll::Ready(Ok(()));
+            }
+
+            reactor.borrow_mut().poll_unsubmitted(context).map(Ok)
+        })
+        .await?;
+    }
+}
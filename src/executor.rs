@@ -0,0 +1,431 @@
+//! Single threaded executor for running many tasks concurrently on top of the
+//! reactor.
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    future::{Future, IntoFuture},
+    io::Result,
+    pin::{pin, Pin},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        Mutex,
+        PoisonError,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, ThreadId},
+};
+
+use thunderdome::{Arena, Index};
+
+use crate::reactor::{self, Reactor, RemoteHandle};
+
+/// Queue of tasks that have been woken up and should be polled.
+///
+/// This needs to be thread safe as wakers are allowed to be sent anywhere.
+type ReadyQueue = Arc<Mutex<VecDeque<Index>>>;
+
+/// Means of interrupting the reactor when woken from another thread, as it
+/// might be blocked waiting for completions.
+#[derive(Clone)]
+struct Interrupt {
+    owner: ThreadId,
+    remote: RemoteHandle,
+}
+
+impl Interrupt {
+    fn trigger(&self) {
+        if thread::current().id() != self.owner {
+            // nothing sensible to do about failures inside of a waker
+            _ = self.remote.wake();
+        }
+    }
+}
+
+/// Waker that schedules a task to be polled.
+///
+/// Spurious duplicates in the ready queue are harmless, so there's no attempt
+/// at deduplicating them.
+struct TaskWaker {
+    index: Index,
+    interrupt: Interrupt,
+    queue: ReadyQueue,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(self.index);
+
+        self.interrupt.trigger();
+    }
+}
+
+/// Waker for the future passed to [`Executor::run_until`].
+struct MainWaker {
+    interrupt: Interrupt,
+    woken: AtomicBool,
+}
+
+impl Wake for MainWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.interrupt.trigger();
+    }
+}
+
+/// Spawned task stored by the executor.
+struct Task {
+    /// Taken out while being polled so that the task is able to spawn more.
+    future: Option<Pin<Box<dyn Future<Output = ()>>>>,
+    waker: Waker,
+}
+
+/// State shared between a spawned task and its [`JoinHandle`].
+struct JoinState<T> {
+    aborted: bool,
+    finished: bool,
+    output: Option<T>,
+    waiter: Option<Waker>,
+}
+
+impl<T> JoinState<T> {
+    fn finish(&mut self, output: Option<T>) {
+        self.output = output;
+        self.finished = true;
+
+        if let Some(waiter) = self.waiter.take() {
+            waiter.wake();
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Wrapper that stores the output of a task for its [`JoinHandle`].
+    struct Spawned<F: Future> {
+        #[pin]
+        future: F,
+        state: Rc<RefCell<JoinState<F::Output>>>,
+    }
+}
+
+impl<F: Future> Future for Spawned<F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if this.state.borrow().aborted {
+            this.state.borrow_mut().finish(None);
+            return Poll::Ready(());
+        }
+
+        let output = std::task::ready!(this.future.poll(cx));
+        this.state.borrow_mut().finish(Some(output));
+
+        Poll::Ready(())
+    }
+}
+
+/// Handle for waiting on the output of a spawned task, which resolves to
+/// [`None`] if the task was aborted.
+///
+/// Dropping the handle detaches the task, leaving it to run in the background.
+#[must_use]
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+    task: Waker,
+}
+
+impl<T> JoinHandle<T> {
+    /// Abort the task, dropping it the next time the executor gets to it.
+    ///
+    /// This does nothing if the task has already finished.
+    pub fn abort(&self) {
+        let mut state = self.state.borrow_mut();
+
+        if !state.finished {
+            state.aborted = true;
+            self.task.wake_by_ref();
+        }
+    }
+
+    /// Check whether the task has either finished or been aborted.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().finished
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+
+        if state.finished {
+            return Poll::Ready(state.output.take());
+        }
+
+        match &mut state.waiter {
+            Some(waiter) => waiter.clone_from(cx.waker()),
+            waiter @ None => *waiter = Some(cx.waker().clone()),
+        }
+
+        Poll::Pending
+    }
+}
+
+/// State shared between the executor and [`spawn_local`].
+struct Shared {
+    interrupt: Interrupt,
+    queue: ReadyQueue,
+    reactor: Rc<RefCell<Reactor>>,
+    tasks: RefCell<Arena<Task>>,
+}
+
+impl Shared {
+    fn has_ready(&self) -> bool {
+        !self
+            .queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+
+    /// Poll every task that was ready when called.
+    fn run_ready(&self) {
+        let ready = std::mem::take(&mut *self.queue.lock().unwrap_or_else(PoisonError::into_inner));
+
+        for &index in &ready {
+            let Some((mut future, waker)) =
+                self.tasks.borrow_mut().get_mut(index).and_then(|task| {
+                    let future = task.future.take()?;
+                    Some((future, task.waker.clone()))
+                })
+            else {
+                // the task has either already finished or is a duplicate
+                continue;
+            };
+
+            if future
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker))
+                .is_ready()
+            {
+                self.tasks.borrow_mut().remove(index);
+            } else {
+                self.tasks.borrow_mut()[index].future = Some(future);
+            }
+        }
+    }
+
+    fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let state = Rc::new(RefCell::new(JoinState {
+            output: None,
+            finished: false,
+            aborted: false,
+            waiter: None,
+        }));
+
+        let future = Spawned {
+            future,
+            state: Rc::clone(&state),
+        };
+
+        // the waker needs to know the index, so it's only filled in afterwards
+        let mut tasks = self.tasks.borrow_mut();
+        let index = tasks.insert(Task {
+            future: Some(Box::pin(future)),
+            waker: Waker::noop().clone(),
+        });
+
+        let task = Waker::from(Arc::new(TaskWaker {
+            index,
+            queue: Arc::clone(&self.queue),
+            interrupt: self.interrupt.clone(),
+        }));
+
+        tasks[index].waker.clone_from(&task);
+        task.wake_by_ref();
+
+        JoinHandle { state, task }
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Shared>>> = const { RefCell::new(None) };
+}
+
+/// Guard for restoring the previously running executor.
+struct Enter {
+    previous: Option<Rc<Shared>>,
+}
+
+impl Enter {
+    fn new(shared: Rc<Shared>) -> Self {
+        let previous = CURRENT.with(|current| current.replace(Some(shared)));
+        Self { previous }
+    }
+}
+
+impl Drop for Enter {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.replace(self.previous.take()));
+    }
+}
+
+/// Executor for running tasks that only drives the reactor when none of them
+/// are able to make progress.
+///
+/// Wakers that get woken from other threads interrupt the reactor through its
+/// [`RemoteHandle`], as it might be blocked waiting for completions.
+#[must_use]
+pub struct Executor {
+    shared: Rc<Shared>,
+}
+
+impl Executor {
+    /// Create an executor that's tied to the current thread.
+    ///
+    /// # Errors
+    ///
+    /// If setting up the remote handle of the reactor fails.
+    pub fn new(reactor: Rc<RefCell<Reactor>>) -> Result<Self> {
+        let interrupt = Interrupt {
+            owner: thread::current().id(),
+            remote: reactor.borrow_mut().remote_handle()?,
+        };
+
+        Ok(Self {
+            shared: Rc::new(Shared {
+                reactor,
+                tasks: RefCell::new(Arena::new()),
+                queue: Arc::default(),
+                interrupt,
+            }),
+        })
+    }
+
+    /// Run spawned tasks until the specified future completes.
+    ///
+    /// Tasks that haven't finished by then are left to be continued on the
+    /// next call, and the reactor is made current while running.
+    ///
+    /// # Errors
+    ///
+    /// If [`Reactor::wait_for_progress`] results in an error.
+    pub fn run_until<F: IntoFuture>(&self, future: F) -> Result<F::Output> {
+        let _enter = Enter::new(Rc::clone(&self.shared));
+        let _reactor = reactor::enter(Rc::clone(&self.shared.reactor));
+
+        let main = Arc::new(MainWaker {
+            woken: AtomicBool::new(true),
+            interrupt: self.shared.interrupt.clone(),
+        });
+
+        let waker = Waker::from(Arc::clone(&main));
+        let mut future = pin!(future.into_future());
+
+        loop {
+            if main.woken.swap(false, Ordering::Acquire) {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker))
+                {
+                    return Ok(output);
+                }
+            }
+
+            self.shared.run_ready();
+
+            if !main.woken.load(Ordering::Acquire) && !self.shared.has_ready() {
+                self.shared.reactor.borrow_mut().wait_for_progress(None)?;
+            }
+        }
+    }
+
+    /// Spawn a task that will be run concurrently with others once the
+    /// executor is running.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        self.shared.spawn(future)
+    }
+}
+
+/// Spawn a task onto the executor that's currently running on this thread.
+///
+/// # Panics
+///
+/// If called outside of [`Executor::run_until`].
+pub fn spawn_local<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    CURRENT.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .expect("spawning a local task requires a running executor")
+            .spawn(future)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, future, io::Result, rc::Rc};
+
+    use super::{spawn_local, Executor};
+    use crate::reactor::Reactor;
+
+    fn executor() -> Result<Executor> {
+        Executor::new(Rc::new(RefCell::new(Reactor::new(8)?)))
+    }
+
+    #[test]
+    fn join_handles_resolve_to_the_output() -> Result<()> {
+        let executor = executor()?;
+        let first = executor.spawn(async { 1 });
+        let second = executor.spawn(async { spawn_local(async { 2 }).await });
+
+        assert!(!first.is_finished());
+        assert_eq!(
+            executor.run_until(async { (first.await, second.await) })?,
+            (Some(1), Some(Some(2))),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn aborted_tasks_resolve_to_none() -> Result<()> {
+        let executor = executor()?;
+        let pending = executor.spawn(future::pending::<()>());
+        let finished = executor.spawn(async { 1 });
+
+        assert_eq!(executor.run_until(finished)?, Some(1));
+        assert!(!pending.is_finished());
+
+        pending.abort();
+        assert_eq!(executor.run_until(pending)?, None);
+
+        Ok(())
+    }
+}
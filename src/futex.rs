@@ -0,0 +1,232 @@
+//! Asynchronous futex waits and wakes, falling back to an in-process waiter
+//! queue on kernels without futex operations.
+//!
+//! The fallback only works for futexes that are private to the process, with
+//! waiters interrupting the reactor they're waiting on through its remote
+//! handle, so that it works the same regardless of which thread wakes them.
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    future::Future,
+    hash::BuildHasherDefault,
+    io::{ErrorKind, Result},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+        Mutex,
+        PoisonError,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use fnv::FnvBuildHasher;
+use io_uring::opcode;
+
+use crate::{
+    operation::{Batch as _, FutexWait, FutexWake, Oneshot as _},
+    reactor::{Reactor, RemoteHandle},
+};
+
+/// Waiters of the fallback implementation by the address of their futex.
+static WAITERS: Mutex<HashMap<usize, VecDeque<Arc<Waiter>>, FnvBuildHasher>> =
+    Mutex::new(HashMap::with_hasher(BuildHasherDefault::new()));
+
+/// Waiter of the fallback implementation.
+struct Waiter {
+    remote: RemoteHandle,
+    waker: Mutex<Option<Waker>>,
+    woken: AtomicBool,
+}
+
+impl Waiter {
+    fn wake(&self) {
+        self.woken.store(true, Ordering::Release);
+
+        let waker = self
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        // the reactor might be blocked regardless of who owns the waker
+        _ = self.remote.wake();
+    }
+}
+
+/// Future for a waiter queued up in the fallback implementation.
+struct Registration {
+    address: usize,
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl Registration {
+    fn new(futex: &AtomicU32, expected: u32, remote: RemoteHandle) -> Self {
+        let address = futex.as_ptr().addr();
+        let mut waiters = WAITERS.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // checked while holding the lock so that wakes in between are seen
+        if futex.load(Ordering::SeqCst) != expected {
+            drop(waiters);
+            return Self {
+                address,
+                waiter: None,
+            };
+        }
+
+        let waiter = Arc::new(Waiter {
+            woken: AtomicBool::new(false),
+            waker: Mutex::new(None),
+            remote,
+        });
+
+        waiters
+            .entry(address)
+            .or_default()
+            .push_back(Arc::clone(&waiter));
+
+        drop(waiters);
+
+        Self {
+            address,
+            waiter: Some(waiter),
+        }
+    }
+
+    fn next_waiter(&self) -> Option<Arc<Waiter>> {
+        let mut waiters = WAITERS.lock().unwrap_or_else(PoisonError::into_inner);
+        let queue = waiters.get_mut(&self.address)?;
+        let next = queue.pop_front();
+
+        if queue.is_empty() {
+            waiters.remove(&self.address);
+        }
+
+        next
+    }
+}
+
+impl Future for Registration {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(waiter) = &self.waiter else {
+            return Poll::Ready(Ok(()));
+        };
+
+        *waiter.waker.lock().unwrap_or_else(PoisonError::into_inner) = Some(cx.waker().clone());
+
+        if !waiter.woken.load(Ordering::Acquire) {
+            return Poll::Pending;
+        }
+
+        self.waiter = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter.take() else {
+            return;
+        };
+
+        let mut waiters = WAITERS.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(queue) = waiters.get_mut(&self.address) {
+            queue.retain(|queued| !Arc::ptr_eq(queued, &waiter));
+
+            if queue.is_empty() {
+                waiters.remove(&self.address);
+            }
+        }
+
+        drop(waiters);
+
+        // pass on a wakeup that would otherwise get lost
+        if waiter.woken.load(Ordering::Acquire) {
+            if let Some(next) = self.next_waiter() {
+                next.wake();
+            }
+        }
+    }
+}
+
+/// Wait until the futex gets woken up, unless it no longer has the expected
+/// value.
+///
+/// Like with regular futexes, this might return spuriously.
+pub async fn wait(reactor: &RefCell<Reactor>, futex: &AtomicU32, expected: u32) -> Result<()> {
+    if !reactor.borrow().supports(opcode::FutexWait::CODE) {
+        let remote = reactor.borrow_mut().remote_handle()?;
+        return Registration::new(futex, expected, remote).await;
+    }
+
+    let result = FutexWait::new(futex, expected)
+        .into_batch()
+        .build_submission(reactor)
+        .await;
+
+    match result {
+        // the value changed before the wait even started
+        Err(error) if error.kind() == ErrorKind::WouldBlock => Ok(()),
+        result => result,
+    }
+}
+
+/// Wake up to the specified number of waiters.
+pub async fn wake(reactor: &RefCell<Reactor>, futex: &AtomicU32, count: u32) -> Result<()> {
+    if !reactor.borrow().supports(opcode::FutexWake::CODE) {
+        wake_fallback(futex, count);
+        return Ok(());
+    }
+
+    FutexWake::new(futex, count)
+        .into_batch()
+        .build_submission(reactor)
+        .await
+}
+
+/// Wake up to the specified number of waiters from a thread that doesn't have
+/// a reactor, covering both the kernel and the fallback implementation.
+pub fn wake_blocking(futex: &AtomicU32, count: u32) {
+    // SAFETY: the futex is kept alive by the reference for the call
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex.as_ptr(),
+            libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+            count,
+        );
+    }
+
+    wake_fallback(futex, count);
+}
+
+fn wake_fallback(futex: &AtomicU32, count: u32) {
+    let woken = {
+        let mut waiters = WAITERS.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(queue) = waiters.get_mut(&futex.as_ptr().addr()) else {
+            return;
+        };
+
+        let count = usize::try_from(count)
+            .unwrap_or(usize::MAX)
+            .min(queue.len());
+        let woken: Vec<_> = queue.drain(..count).collect();
+
+        if queue.is_empty() {
+            waiters.remove(&futex.as_ptr().addr());
+        }
+
+        woken
+    };
+
+    for waiter in woken {
+        waiter.wake();
+    }
+}
@@ -15,8 +15,14 @@ use std::{cell::RefCell, future::IntoFuture, io::Result};
 use crate::reactor::Reactor;
 
 pub mod adapter;
+pub mod blocking;
+pub mod embed;
+pub mod executor;
+mod futex;
+pub mod net;
 pub mod operation;
 pub mod reactor;
+pub mod runtime;
 pub mod synchronization;
 
 /// Block on the future by polling it concurrently with driving the reactor.
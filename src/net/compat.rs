@@ -0,0 +1,56 @@
+//! Trait implementations for interoperating with `tokio`.
+use std::{
+    io::Result,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::net::{TcpStream, UnixStream};
+
+/// Helper macro to implement the traits for the stream types that all share
+/// the same set of inherent polling methods.
+macro_rules! impl_tokio_traits {
+    ($($name:ident)*) => {
+        $(
+            impl AsyncRead for $name {
+                fn poll_read(
+                    self: Pin<&mut Self>,
+                    cx: &mut Context<'_>,
+                    buf: &mut ReadBuf<'_>,
+                ) -> Poll<Result<()>> {
+                    let amount = std::task::ready!(
+                        self.get_mut().poll_read(cx, buf.initialize_unfilled())
+                    )?;
+
+                    buf.advance(amount);
+                    Poll::Ready(Ok(()))
+                }
+            }
+
+            impl AsyncWrite for $name {
+                fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                    self.get_mut().poll_flush(cx)
+                }
+
+                fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                    self.get_mut().poll_shutdown(cx)
+                }
+
+                fn poll_write(
+                    self: Pin<&mut Self>,
+                    cx: &mut Context<'_>,
+                    buf: &[u8],
+                ) -> Poll<Result<usize>> {
+                    self.get_mut().poll_write(cx, buf)
+                }
+            }
+        )*
+    };
+}
+
+impl_tokio_traits! {
+    TcpStream
+    UnixStream
+}
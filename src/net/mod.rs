@@ -0,0 +1,12 @@
+//! High-level networking types built on top of the ring operations.
+#[cfg(feature = "tokio-compat")]
+mod compat;
+mod tcp;
+mod udp;
+mod unix;
+
+pub use self::{
+    tcp::{TcpListener, TcpStream},
+    udp::UdpSocket,
+    unix::{Credentials, UnixDatagram, UnixListener, UnixStream},
+};
@@ -0,0 +1,206 @@
+use std::{
+    cell::RefCell,
+    io::Result,
+    net::{self, Shutdown, SocketAddr, ToSocketAddrs},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use crate::{
+    adapter::Bridge,
+    operation::{self, Accept, Batch as _, Connect, Oneshot as _, Recv, Send, Socket},
+    reactor::Reactor,
+};
+
+/// Socket listening for incoming TCP connections.
+#[must_use]
+pub struct TcpListener {
+    inner: net::TcpListener,
+    reactor: Rc<RefCell<Reactor>>,
+}
+
+impl TcpListener {
+    /// Wait for an incoming connection.
+    ///
+    /// # Errors
+    ///
+    /// If accepting the connection fails.
+    pub async fn accept(&self) -> Result<(TcpStream, SocketAddr)> {
+        let socket = Accept::new(self.inner.as_fd())
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await?;
+
+        let stream = TcpStream::new(Rc::clone(&self.reactor), net::TcpStream::from(socket));
+
+        let address = stream.peer_addr()?;
+        Ok((stream, address))
+    }
+
+    /// Bind to the first working address out of the specified ones.
+    ///
+    /// # Errors
+    ///
+    /// If none of the addresses could be bound to.
+    pub fn bind<A: ToSocketAddrs>(reactor: Rc<RefCell<Reactor>>, address: A) -> Result<Self> {
+        let inner = net::TcpListener::bind(address)?;
+        Ok(Self { inner, reactor })
+    }
+
+    /// Query the address the socket is bound to.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+}
+
+impl AsFd for TcpListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// Connected TCP socket that does all IO through the reactor.
+#[must_use]
+pub struct TcpStream {
+    bridge: Bridge,
+    inner: net::TcpStream,
+    reactor: Rc<RefCell<Reactor>>,
+}
+
+impl TcpStream {
+    /// Open a connection to the specified address.
+    ///
+    /// # Errors
+    ///
+    /// If creating the socket or connecting fails.
+    pub async fn connect(reactor: Rc<RefCell<Reactor>>, address: SocketAddr) -> Result<Self> {
+        let socket = Socket::stream_for(&address)
+            .into_batch()
+            .build_submission(&reactor)
+            .await?;
+
+        Connect::new(socket.as_fd(), &address)
+            .into_batch()
+            .build_submission(&reactor)
+            .await?;
+
+        Ok(Self::new(reactor, net::TcpStream::from(socket)))
+    }
+
+    /// Query the local address of the connection.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    fn new(reactor: Rc<RefCell<Reactor>>, inner: net::TcpStream) -> Self {
+        Self {
+            bridge: Bridge::socket(Rc::clone(&reactor)),
+            reactor,
+            inner,
+        }
+    }
+
+    /// Query the remote address of the connection.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Read data into the spare capacity of the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If receiving fails.
+    pub async fn read(&self, buffer: Vec<u8>) -> Result<Vec<u8>> {
+        Recv::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Shut down the read, write, or both halves of the connection.
+    ///
+    /// # Errors
+    ///
+    /// If the shutdown fails.
+    pub async fn shutdown(&self, how: Shutdown) -> Result<()> {
+        operation::Shutdown::new(self.inner.as_fd(), how)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Write data from the buffer, returning how much was written along with
+    /// the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If sending fails.
+    pub async fn write(&self, buffer: Vec<u8>) -> Result<(usize, Vec<u8>)> {
+        Send::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+}
+
+// the trait implementations just forward to these inherent methods
+#[cfg_attr(feature = "tokio-compat", expect(clippy::same_name_method))]
+impl TcpStream {
+    /// Poll for all previously written data to be sent.
+    pub fn poll_flush(&mut self, context: &Context) -> Poll<Result<()>> {
+        self.bridge.poll_flush(self.inner.as_fd(), context)
+    }
+
+    /// Poll for data to be read into the specified buffer.
+    ///
+    /// This keeps an internal buffer in flight, so data isn't lost even if
+    /// polling stops midway.
+    pub fn poll_read(&mut self, context: &Context, buffer: &mut [u8]) -> Poll<Result<usize>> {
+        self.bridge.poll_read(self.inner.as_fd(), context, buffer)
+    }
+
+    /// Poll for all previously written data to be sent and the write half of
+    /// the connection to be shut down.
+    pub fn poll_shutdown(&mut self, context: &Context) -> Poll<Result<()>> {
+        self.bridge.poll_shutdown(self.inner.as_fd(), context)
+    }
+
+    /// Poll for data to be written from the specified buffer.
+    ///
+    /// The data is copied into an internal buffer, so this only waits for any
+    /// previous write to finish.
+    pub fn poll_write(&mut self, context: &Context, buffer: &[u8]) -> Poll<Result<usize>> {
+        self.bridge.poll_write(self.inner.as_fd(), context, buffer)
+    }
+}
+
+impl AsFd for TcpStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    cell::RefCell,
+    io::{ErrorKind, Result},
+    net::{self, SocketAddr, ToSocketAddrs},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    rc::Rc,
+};
+
+use crate::{
+    operation::{Batch as _, Multiple, Oneshot as _, Recv, RecvMsg, Send, SendMsg},
+    reactor::Reactor,
+};
+
+/// UDP socket that does all IO through the reactor.
+#[must_use]
+pub struct UdpSocket {
+    inner: net::UdpSocket,
+    reactor: Rc<RefCell<Reactor>>,
+}
+
+impl UdpSocket {
+    /// Bind to the first working address out of the specified ones.
+    ///
+    /// # Errors
+    ///
+    /// If none of the addresses could be bound to.
+    pub fn bind<A: ToSocketAddrs>(reactor: Rc<RefCell<Reactor>>, address: A) -> Result<Self> {
+        let inner = net::UdpSocket::bind(address)?;
+        Ok(Self { inner, reactor })
+    }
+
+    /// Set the default destination and limit incoming datagrams to it.
+    ///
+    /// # Errors
+    ///
+    /// If none of the addresses could be connected to.
+    pub fn connect<A: ToSocketAddrs>(&self, address: A) -> Result<()> {
+        self.inner.connect(address)
+    }
+
+    /// Query the address the socket is bound to.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Query the address the socket is connected to.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Receive a datagram from the connected address into the spare capacity
+    /// of the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If receiving fails.
+    pub async fn recv(&self, buffer: Vec<u8>) -> Result<Vec<u8>> {
+        Recv::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Receive a datagram into the spare capacity of the buffer along with the
+    /// address it was sent from.
+    ///
+    /// # Errors
+    ///
+    /// If receiving fails, with [`Truncated`] as the inner error when the
+    /// datagram didn't fit into the spare capacity of the buffer.
+    ///
+    /// [`Truncated`]: crate::operation::Truncated
+    pub async fn recv_from(&self, buffer: Vec<u8>) -> Result<(Vec<u8>, SocketAddr)> {
+        RecvMsg::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Receive a datagram into the first buffer, followed by one datagram
+    /// into each of the remaining buffers for as many as are already queued,
+    /// submitting all of those receives at once.
+    ///
+    /// This resolves as soon as the first receive completes, so it never
+    /// waits for more datagrams than have arrived. Buffers left without a
+    /// datagram are dropped and have no corresponding result, and nothing
+    /// else is received after the first receive fails.
+    pub async fn recv_from_batch<I>(&self, buffers: I) -> Vec<Result<(Vec<u8>, SocketAddr)>>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        let mut buffers = buffers.into_iter();
+
+        let Some(first) = buffers.next() else {
+            return Vec::new();
+        };
+
+        let first = self.recv_from(first).await;
+
+        if first.is_err() {
+            return vec![first];
+        }
+
+        let socket = self.inner.as_fd();
+        let operations = buffers.map(|buffer| RecvMsg::new(socket, buffer).nonblocking());
+
+        let queued = Multiple::new(operations)
+            .build_submission(&self.reactor)
+            .await;
+
+        std::iter::once(first)
+            .chain(queued.into_iter().filter(
+                |result| !matches!(result, Err(error) if error.kind() == ErrorKind::WouldBlock),
+            ))
+            .collect()
+    }
+
+    /// Send a datagram to the connected address.
+    ///
+    /// # Errors
+    ///
+    /// If sending fails.
+    pub async fn send(&self, buffer: Vec<u8>) -> Result<(usize, Vec<u8>)> {
+        Send::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Send a datagram to the specified address.
+    ///
+    /// # Errors
+    ///
+    /// If sending fails.
+    pub async fn send_to(&self, buffer: Vec<u8>, address: SocketAddr) -> Result<(usize, Vec<u8>)> {
+        SendMsg::to(self.inner.as_fd(), buffer, &address)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Send every datagram to its specified address, submitting all of them
+    /// at once.
+    pub async fn send_to_batch<I>(&self, datagrams: I) -> Vec<Result<(usize, Vec<u8>)>>
+    where
+        I: IntoIterator<Item = (Vec<u8>, SocketAddr)>,
+    {
+        let socket = self.inner.as_fd();
+        let operations = datagrams
+            .into_iter()
+            .map(|(buffer, address)| SendMsg::to(socket, buffer, &address));
+
+        Multiple::new(operations)
+            .build_submission(&self.reactor)
+            .await
+    }
+}
+
+impl AsFd for UdpSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
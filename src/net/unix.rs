@@ -0,0 +1,569 @@
+use std::{
+    cell::RefCell,
+    io::{Error, ErrorKind, Result},
+    net::Shutdown,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        unix::net::{self, SocketAddr},
+    },
+    path::Path,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use crate::{
+    adapter::Bridge,
+    operation::{
+        self,
+        Accept,
+        Batch as _,
+        Connect,
+        Multiple,
+        Oneshot as _,
+        Recv,
+        RecvFds,
+        RecvMsg,
+        Send,
+        SendMsg,
+        Socket,
+    },
+    reactor::Reactor,
+};
+
+/// Credentials of the process on the other end of a unix socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct Credentials {
+    group: libc::gid_t,
+    process: libc::pid_t,
+    user: libc::uid_t,
+}
+
+impl Credentials {
+    #[must_use]
+    pub const fn group(&self) -> libc::gid_t {
+        self.group
+    }
+
+    #[must_use]
+    pub const fn process(&self) -> libc::pid_t {
+        self.process
+    }
+
+    /// Query the credentials the peer had when the connection was made.
+    fn query(socket: BorrowedFd) -> Result<Self> {
+        let mut raw = libc::ucred {
+            pid: 0,
+            uid: 0,
+            gid: 0,
+        };
+
+        let mut length = size_of_val(&raw).try_into().unwrap_or(libc::socklen_t::MAX);
+
+        // SAFETY: the output pointers point at appropriately sized values
+        let result = unsafe {
+            libc::getsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                (&raw mut raw).cast(),
+                &raw mut length,
+            )
+        };
+
+        if result.is_negative() {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(Self {
+            process: raw.pid,
+            user: raw.uid,
+            group: raw.gid,
+        })
+    }
+
+    #[must_use]
+    pub const fn user(&self) -> libc::uid_t {
+        self.user
+    }
+}
+
+/// Unix socket listening for incoming stream connections.
+#[must_use]
+pub struct UnixListener {
+    inner: net::UnixListener,
+    reactor: Rc<RefCell<Reactor>>,
+}
+
+impl UnixListener {
+    /// Wait for an incoming connection.
+    ///
+    /// # Errors
+    ///
+    /// If accepting the connection fails.
+    pub async fn accept(&self) -> Result<(UnixStream, SocketAddr)> {
+        let socket = Accept::new(self.inner.as_fd())
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await?;
+
+        let stream = UnixStream::new(Rc::clone(&self.reactor), net::UnixStream::from(socket));
+
+        let address = stream.peer_addr()?;
+        Ok((stream, address))
+    }
+
+    /// Bind to the specified filesystem path.
+    ///
+    /// # Errors
+    ///
+    /// If the path can't be bound to.
+    pub fn bind<P: AsRef<Path>>(reactor: Rc<RefCell<Reactor>>, path: P) -> Result<Self> {
+        let inner = net::UnixListener::bind(path)?;
+        Ok(Self { inner, reactor })
+    }
+
+    /// Bind to the specified address, which also allows for abstract names.
+    ///
+    /// # Errors
+    ///
+    /// If the address can't be bound to.
+    pub fn bind_addr(reactor: Rc<RefCell<Reactor>>, address: &SocketAddr) -> Result<Self> {
+        let inner = net::UnixListener::bind_addr(address)?;
+        Ok(Self { inner, reactor })
+    }
+
+    /// Query the address the socket is bound to.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+}
+
+impl AsFd for UnixListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// Connected unix stream socket that does all IO through the reactor.
+#[must_use]
+pub struct UnixStream {
+    bridge: Bridge,
+    inner: net::UnixStream,
+    reactor: Rc<RefCell<Reactor>>,
+}
+
+impl UnixStream {
+    /// Open a connection to the specified filesystem path.
+    ///
+    /// # Errors
+    ///
+    /// If the path is invalid, or creating the socket or connecting fails.
+    pub async fn connect<P: AsRef<Path>>(reactor: Rc<RefCell<Reactor>>, path: P) -> Result<Self> {
+        let address = SocketAddr::from_pathname(path)?;
+        Self::connect_addr(reactor, &address).await
+    }
+
+    /// Open a connection to the specified address, which also allows for
+    /// abstract names.
+    ///
+    /// # Errors
+    ///
+    /// If creating the socket or connecting fails.
+    pub async fn connect_addr(reactor: Rc<RefCell<Reactor>>, address: &SocketAddr) -> Result<Self> {
+        let socket = Socket::new(libc::AF_UNIX, libc::SOCK_STREAM, 0)
+            .into_batch()
+            .build_submission(&reactor)
+            .await?;
+
+        Connect::new(socket.as_fd(), address)
+            .into_batch()
+            .build_submission(&reactor)
+            .await?;
+
+        Ok(Self::new(reactor, net::UnixStream::from(socket)))
+    }
+
+    /// Query the local address of the connection.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    fn new(reactor: Rc<RefCell<Reactor>>, inner: net::UnixStream) -> Self {
+        Self {
+            bridge: Bridge::socket(Rc::clone(&reactor)),
+            reactor,
+            inner,
+        }
+    }
+
+    /// Create a pair of connected sockets.
+    ///
+    /// # Errors
+    ///
+    /// If creating the sockets fails.
+    pub fn pair(reactor: &Rc<RefCell<Reactor>>) -> Result<(Self, Self)> {
+        let (first, second) = net::UnixStream::pair()?;
+
+        Ok((
+            Self::new(Rc::clone(reactor), first),
+            Self::new(Rc::clone(reactor), second),
+        ))
+    }
+
+    /// Query the remote address of the connection.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Query the credentials of the process on the other end.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn peer_credentials(&self) -> Result<Credentials> {
+        Credentials::query(self.inner.as_fd())
+    }
+
+    /// Read data into the spare capacity of the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If receiving fails.
+    pub async fn read(&self, buffer: Vec<u8>) -> Result<Vec<u8>> {
+        Recv::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Read data into the spare capacity of the buffer, accepting up to the
+    /// specified amount of descriptors passed along with it.
+    ///
+    /// # Errors
+    ///
+    /// If receiving fails, with [`Truncated`] as the inner error when more
+    /// descriptors were passed than there was space for.
+    ///
+    /// [`Truncated`]: crate::operation::Truncated
+    pub async fn recv_fds(
+        &self,
+        buffer: Vec<u8>,
+        descriptors: usize,
+    ) -> Result<(Vec<u8>, Vec<OwnedFd>)> {
+        RecvFds::new(self.inner.as_fd(), buffer, descriptors)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Write data from the buffer along with the descriptors.
+    ///
+    /// The buffer must contain at least one byte for the descriptors to
+    /// actually get passed.
+    ///
+    /// # Errors
+    ///
+    /// If sending fails.
+    pub async fn send_fds(
+        &self,
+        buffer: Vec<u8>,
+        descriptors: &[BorrowedFd<'_>],
+    ) -> Result<(usize, Vec<u8>)> {
+        SendMsg::new(self.inner.as_fd(), buffer)
+            .with_fds(descriptors)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Shut down the read, write, or both halves of the connection.
+    ///
+    /// # Errors
+    ///
+    /// If the shutdown fails.
+    pub async fn shutdown(&self, how: Shutdown) -> Result<()> {
+        operation::Shutdown::new(self.inner.as_fd(), how)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Write data from the buffer, returning how much was written along with
+    /// the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If sending fails.
+    pub async fn write(&self, buffer: Vec<u8>) -> Result<(usize, Vec<u8>)> {
+        Send::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+}
+
+// the trait implementations just forward to these inherent methods
+#[cfg_attr(feature = "tokio-compat", expect(clippy::same_name_method))]
+impl UnixStream {
+    /// Poll for all previously written data to be sent.
+    pub fn poll_flush(&mut self, context: &Context) -> Poll<Result<()>> {
+        self.bridge.poll_flush(self.inner.as_fd(), context)
+    }
+
+    /// Poll for data to be read into the specified buffer.
+    ///
+    /// This keeps an internal buffer in flight, so data isn't lost even if
+    /// polling stops midway.
+    pub fn poll_read(&mut self, context: &Context, buffer: &mut [u8]) -> Poll<Result<usize>> {
+        self.bridge.poll_read(self.inner.as_fd(), context, buffer)
+    }
+
+    /// Poll for all previously written data to be sent and the write half of
+    /// the connection to be shut down.
+    pub fn poll_shutdown(&mut self, context: &Context) -> Poll<Result<()>> {
+        self.bridge.poll_shutdown(self.inner.as_fd(), context)
+    }
+
+    /// Poll for data to be written from the specified buffer.
+    ///
+    /// The data is copied into an internal buffer, so this only waits for any
+    /// previous write to finish.
+    pub fn poll_write(&mut self, context: &Context, buffer: &[u8]) -> Poll<Result<usize>> {
+        self.bridge.poll_write(self.inner.as_fd(), context, buffer)
+    }
+}
+
+impl AsFd for UnixStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl AsRawFd for UnixStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// Unix datagram socket that does all IO through the reactor.
+#[must_use]
+pub struct UnixDatagram {
+    inner: net::UnixDatagram,
+    reactor: Rc<RefCell<Reactor>>,
+}
+
+impl UnixDatagram {
+    /// Bind to the specified filesystem path.
+    ///
+    /// # Errors
+    ///
+    /// If the path can't be bound to.
+    pub fn bind<P: AsRef<Path>>(reactor: Rc<RefCell<Reactor>>, path: P) -> Result<Self> {
+        let inner = net::UnixDatagram::bind(path)?;
+        Ok(Self { inner, reactor })
+    }
+
+    /// Bind to the specified address, which also allows for abstract names.
+    ///
+    /// # Errors
+    ///
+    /// If the address can't be bound to.
+    pub fn bind_addr(reactor: Rc<RefCell<Reactor>>, address: &SocketAddr) -> Result<Self> {
+        let inner = net::UnixDatagram::bind_addr(address)?;
+        Ok(Self { inner, reactor })
+    }
+
+    /// Set the default destination and limit incoming datagrams to it.
+    ///
+    /// # Errors
+    ///
+    /// If connecting fails.
+    pub fn connect_addr(&self, address: &SocketAddr) -> Result<()> {
+        self.inner.connect_addr(address)
+    }
+
+    /// Query the address the socket is bound to.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Create a pair of connected sockets.
+    ///
+    /// # Errors
+    ///
+    /// If creating the sockets fails.
+    pub fn pair(reactor: &Rc<RefCell<Reactor>>) -> Result<(Self, Self)> {
+        let (first, second) = net::UnixDatagram::pair()?;
+
+        Ok((
+            Self {
+                reactor: Rc::clone(reactor),
+                inner: first,
+            },
+            Self {
+                reactor: Rc::clone(reactor),
+                inner: second,
+            },
+        ))
+    }
+
+    /// Query the address the socket is connected to.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Receive a datagram into the spare capacity of the buffer.
+    ///
+    /// # Errors
+    ///
+    /// If receiving fails.
+    pub async fn recv(&self, buffer: Vec<u8>) -> Result<Vec<u8>> {
+        Recv::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Receive a datagram into the spare capacity of the buffer along with the
+    /// address it was sent from.
+    ///
+    /// # Errors
+    ///
+    /// If receiving fails, with [`Truncated`] as the inner error when the
+    /// datagram didn't fit into the spare capacity of the buffer.
+    ///
+    /// [`Truncated`]: crate::operation::Truncated
+    pub async fn recv_from(&self, buffer: Vec<u8>) -> Result<(Vec<u8>, SocketAddr)> {
+        RecvMsg::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Receive a datagram into the first buffer, followed by one datagram
+    /// into each of the remaining buffers for as many as are already queued,
+    /// submitting all of those receives at once.
+    ///
+    /// This resolves as soon as the first receive completes, so it never
+    /// waits for more datagrams than have arrived. Buffers left without a
+    /// datagram are dropped and have no corresponding result, and nothing
+    /// else is received after the first receive fails.
+    pub async fn recv_from_batch<I>(&self, buffers: I) -> Vec<Result<(Vec<u8>, SocketAddr)>>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        let mut buffers = buffers.into_iter();
+
+        let Some(first) = buffers.next() else {
+            return Vec::new();
+        };
+
+        let first = self.recv_from(first).await;
+
+        if first.is_err() {
+            return vec![first];
+        }
+
+        let socket = self.inner.as_fd();
+        let operations = buffers.map(|buffer| RecvMsg::new(socket, buffer).nonblocking());
+
+        let queued = Multiple::new(operations)
+            .build_submission(&self.reactor)
+            .await;
+
+        std::iter::once(first)
+            .chain(queued.into_iter().filter(
+                |result| !matches!(result, Err(error) if error.kind() == ErrorKind::WouldBlock),
+            ))
+            .collect()
+    }
+
+    /// Send a datagram to the connected address.
+    ///
+    /// # Errors
+    ///
+    /// If sending fails.
+    pub async fn send(&self, buffer: Vec<u8>) -> Result<(usize, Vec<u8>)> {
+        Send::new(self.inner.as_fd(), buffer)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Send a datagram to the specified address.
+    ///
+    /// # Errors
+    ///
+    /// If sending fails.
+    pub async fn send_to(&self, buffer: Vec<u8>, address: &SocketAddr) -> Result<(usize, Vec<u8>)> {
+        SendMsg::to(self.inner.as_fd(), buffer, address)
+            .into_batch()
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Send every datagram to its specified address, submitting all of them
+    /// at once.
+    pub async fn send_to_batch<I>(&self, datagrams: I) -> Vec<Result<(usize, Vec<u8>)>>
+    where
+        I: IntoIterator<Item = (Vec<u8>, SocketAddr)>,
+    {
+        let socket = self.inner.as_fd();
+        let operations = datagrams
+            .into_iter()
+            .map(|(buffer, address)| SendMsg::to(socket, buffer, &address));
+
+        Multiple::new(operations)
+            .build_submission(&self.reactor)
+            .await
+    }
+
+    /// Create a socket that isn't bound to any address.
+    ///
+    /// # Errors
+    ///
+    /// If creating the socket fails.
+    pub fn unbound(reactor: Rc<RefCell<Reactor>>) -> Result<Self> {
+        let inner = net::UnixDatagram::unbound()?;
+        Ok(Self { inner, reactor })
+    }
+}
+
+impl AsFd for UnixDatagram {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl AsRawFd for UnixDatagram {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
@@ -0,0 +1,270 @@
+// submodule for socket address constants to allow the casts from libc's weird
+// type choices
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation)]
+mod raw {
+    pub const INET: libc::sa_family_t = libc::AF_INET as libc::sa_family_t;
+    pub const INET6: libc::sa_family_t = libc::AF_INET6 as libc::sa_family_t;
+    pub const UNIX: libc::sa_family_t = libc::AF_UNIX as libc::sa_family_t;
+    pub const INET_LENGTH: libc::socklen_t = size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    pub const INET6_LENGTH: libc::socklen_t = size_of::<libc::sockaddr_in6>() as libc::socklen_t;
+    pub const STORAGE_LENGTH: libc::socklen_t =
+        size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    pub const UNIX_PATH_OFFSET: usize = std::mem::offset_of!(libc::sockaddr_un, sun_path);
+}
+
+use std::{
+    ffi::OsStr,
+    io::{Error, ErrorKind, Result},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    os::{
+        linux::net::SocketAddrExt as _,
+        unix::{ffi::OsStrExt as _, net},
+    },
+};
+
+/// Socket address in the representation expected by the kernel.
+#[must_use]
+pub struct RawAddress {
+    length: libc::socklen_t,
+    storage: libc::sockaddr_storage,
+}
+
+impl RawAddress {
+    /// Mutable pointer to the address for the kernel to write into.
+    pub(crate) const fn as_mut_ptr(&mut self) -> *mut libc::sockaddr {
+        (&raw mut self.storage).cast()
+    }
+
+    /// Pointer to the address for passing it to the kernel.
+    pub(crate) const fn as_ptr(&self) -> *const libc::sockaddr {
+        (&raw const self.storage).cast()
+    }
+
+    /// Create storage large enough for the kernel to write any address into.
+    pub const fn empty() -> Self {
+        Self {
+            // SAFETY: all zeroes is a valid socket address storage
+            storage: unsafe { std::mem::zeroed() },
+            length: raw::STORAGE_LENGTH,
+        }
+    }
+
+    const fn family(&self) -> libc::sa_family_t {
+        self.storage.ss_family
+    }
+
+    /// Length of the address in bytes.
+    pub(crate) const fn len(&self) -> libc::socklen_t {
+        self.length
+    }
+
+    /// Read the storage as a concrete address structure.
+    const fn load<T: Copy>(&self) -> T {
+        assert!(size_of::<T>() <= size_of::<libc::sockaddr_storage>());
+
+        // SAFETY: the storage is large enough and suitably aligned, and callers
+        // only ask for plain address structures valid for any bit pattern
+        unsafe { std::ptr::read((&raw const self.storage).cast()) }
+    }
+
+    /// Update the length after the kernel has written into the storage.
+    pub(crate) const fn set_len(&mut self, length: libc::socklen_t) {
+        self.length = length;
+    }
+
+    /// Write a concrete address structure into the storage.
+    const fn store<T>(&mut self, value: T, length: libc::socklen_t) {
+        assert!(size_of::<T>() <= size_of::<libc::sockaddr_storage>());
+
+        // SAFETY: the storage is large enough and suitably aligned
+        unsafe { std::ptr::write((&raw mut self.storage).cast(), value) };
+        self.length = length;
+    }
+}
+
+/// Address types that can be passed to and from the kernel.
+pub trait SocketAddress: Sized {
+    /// Convert from the kernel representation.
+    ///
+    /// # Errors
+    ///
+    /// If the address belongs to another family or is otherwise malformed.
+    fn decode(address: &RawAddress) -> Result<Self>;
+
+    /// Convert into the kernel representation.
+    fn encode(&self) -> RawAddress;
+}
+
+impl SocketAddress for SocketAddr {
+    fn decode(address: &RawAddress) -> Result<Self> {
+        match address.family() {
+            raw::INET if address.len() >= raw::INET_LENGTH => {
+                let inner = address.load::<libc::sockaddr_in>();
+                let ip = Ipv4Addr::from(inner.sin_addr.s_addr.to_ne_bytes());
+                Ok(SocketAddrV4::new(ip, u16::from_be(inner.sin_port)).into())
+            }
+            raw::INET6 if address.len() >= raw::INET6_LENGTH => {
+                let inner = address.load::<libc::sockaddr_in6>();
+                let ip = Ipv6Addr::from(inner.sin6_addr.s6_addr);
+                let port = u16::from_be(inner.sin6_port);
+                Ok(SocketAddrV6::new(ip, port, inner.sin6_flowinfo, inner.sin6_scope_id).into())
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "not an internet socket address",
+            )),
+        }
+    }
+
+    fn encode(&self) -> RawAddress {
+        let mut address = RawAddress::empty();
+
+        match self {
+            Self::V4(inner) => address.store(
+                libc::sockaddr_in {
+                    sin_family: raw::INET,
+                    sin_port: inner.port().to_be(),
+                    sin_addr: libc::in_addr {
+                        s_addr: u32::from_ne_bytes(inner.ip().octets()),
+                    },
+                    sin_zero: [0; 8],
+                },
+                raw::INET_LENGTH,
+            ),
+            Self::V6(inner) => address.store(
+                libc::sockaddr_in6 {
+                    sin6_family: raw::INET6,
+                    sin6_port: inner.port().to_be(),
+                    sin6_flowinfo: inner.flowinfo(),
+                    sin6_addr: libc::in6_addr {
+                        s6_addr: inner.ip().octets(),
+                    },
+                    sin6_scope_id: inner.scope_id(),
+                },
+                raw::INET6_LENGTH,
+            ),
+        }
+
+        address
+    }
+}
+
+impl SocketAddress for net::SocketAddr {
+    fn decode(address: &RawAddress) -> Result<Self> {
+        // unnamed sockets might not even get their family filled in
+        if usize::try_from(address.len()).is_ok_and(|length| length <= raw::UNIX_PATH_OFFSET) {
+            return Self::from_pathname("");
+        }
+
+        if address.family() != raw::UNIX {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "not an unix socket address",
+            ));
+        }
+
+        let inner = address.load::<libc::sockaddr_un>();
+        let length = usize::try_from(address.len()).unwrap_or(usize::MAX);
+        let name_length = length
+            .saturating_sub(raw::UNIX_PATH_OFFSET)
+            .min(inner.sun_path.len());
+
+        let name = inner.sun_path[..name_length]
+            .iter()
+            .map(|byte| byte.cast_unsigned())
+            .collect::<Vec<_>>();
+
+        if let Some((0, name)) = name.split_first() {
+            return Self::from_abstract_name(name);
+        }
+
+        let end = name
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(name.len());
+        Self::from_pathname(OsStr::from_bytes(&name[..end]))
+    }
+
+    fn encode(&self) -> RawAddress {
+        // SAFETY: all zeroes is a valid unix socket address
+        let mut inner: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+        inner.sun_family = raw::UNIX;
+
+        // abstract names are distinguished by a leading null byte
+        let (offset, name) = match (self.as_pathname(), self.as_abstract_name()) {
+            (Some(path), _) => (0, path.as_os_str().as_bytes()),
+            (None, Some(name)) => (1, name),
+            (None, None) => (0, [].as_slice()),
+        };
+
+        for (target, byte) in inner.sun_path[offset..].iter_mut().zip(name) {
+            *target = byte.cast_signed();
+        }
+
+        // pathnames include the terminating null byte while the others don't
+        let terminator = usize::from(self.as_pathname().is_some());
+        let length = raw::UNIX_PATH_OFFSET + offset + name.len() + terminator;
+
+        let mut address = RawAddress::empty();
+        address.store(inner, length.try_into().unwrap_or(libc::socklen_t::MAX));
+        address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{ErrorKind, Result},
+        net::SocketAddr,
+        os::{linux::net::SocketAddrExt as _, unix::net},
+    };
+
+    use super::{RawAddress, SocketAddress as _};
+
+    #[test]
+    fn internet_addresses_round_trip() -> Result<()> {
+        for address in ["127.0.0.1:8080", "[::1]:443", "[fe80::1%2]:53"] {
+            let address = address
+                .parse::<SocketAddr>()
+                .map_err(std::io::Error::other)?;
+            assert_eq!(SocketAddr::decode(&address.encode())?, address);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unix_addresses_round_trip() -> Result<()> {
+        let addresses = [
+            net::SocketAddr::from_pathname("/tmp/socket")?,
+            net::SocketAddr::from_abstract_name(b"abstract")?,
+            net::SocketAddr::from_pathname("")?,
+        ];
+
+        for address in addresses {
+            let decoded = net::SocketAddr::decode(&address.encode())?;
+
+            assert_eq!(decoded.as_pathname(), address.as_pathname());
+            assert_eq!(decoded.as_abstract_name(), address.as_abstract_name());
+            assert_eq!(decoded.is_unnamed(), address.is_unnamed());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn other_families_are_rejected() -> Result<()> {
+        let unix = net::SocketAddr::from_pathname("/tmp/socket")?.encode();
+        let error = SocketAddr::decode(&unix).err();
+        assert_eq!(
+            error.map(|error| error.kind()),
+            Some(ErrorKind::InvalidInput)
+        );
+
+        let mut empty = RawAddress::empty();
+        empty.set_len(0);
+        assert!(net::SocketAddr::decode(&empty)?.is_unnamed());
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     any::Any,
     cell::RefCell,
+    io::{Error, Result},
     task::{Context, Poll},
 };
 
@@ -129,4 +130,33 @@ pub unsafe trait Batch {
     {
         SubmitAndWait::new(reactor, self)
     }
+
+    /// Create a submission future for the current reactor.
+    ///
+    /// # Panics
+    ///
+    /// If there's no current reactor.
+    fn submit(self) -> SubmitAndWait<'static, Self>
+    where
+        Self: Batch + Sized,
+    {
+        SubmitAndWait::with_current(self)
+    }
+}
+
+/// Output that can also represent an operation failing without ever being
+/// submitted, such as when the kernel doesn't support its opcode.
+///
+/// This is what allows the wrappers turning operations into a [`Batch`] to
+/// surface such failures.
+pub trait Fallible {
+    /// Produce the output for the failure.
+    #[must_use]
+    fn from_failure(error: Error) -> Self;
+}
+
+impl<T> Fallible for Result<T> {
+    fn from_failure(error: Error) -> Self {
+        Err(error)
+    }
 }
@@ -2,23 +2,57 @@ use std::{
     cell::RefCell,
     future::Future,
     pin::Pin,
+    rc::Rc,
     task::{Context, Poll},
 };
 
-use crate::{operation::Batch, reactor::Reactor};
+use crate::{
+    operation::Batch,
+    reactor::{self, Reactor},
+};
+
+/// Either an explicitly passed reactor or the current one.
+enum ReactorRef<'reactor> {
+    Borrowed(&'reactor RefCell<Reactor>),
+    Current(Rc<RefCell<Reactor>>),
+}
+
+impl ReactorRef<'_> {
+    fn get(&self) -> &RefCell<Reactor> {
+        match self {
+            Self::Borrowed(reactor) => reactor,
+            Self::Current(reactor) => reactor,
+        }
+    }
+}
 
 /// Future for submitting and waiting for a [`Batch`] to complete.
 #[must_use]
 pub struct SubmitAndWait<'reactor, B: Batch> {
-    reactor: &'reactor RefCell<Reactor>,
     batch: B,
     handle: Option<B::Handle>,
+    reactor: ReactorRef<'reactor>,
 }
 
 impl<'reactor, B: Batch> SubmitAndWait<'reactor, B> {
     pub const fn new(reactor: &'reactor RefCell<Reactor>, batch: B) -> Self {
         Self {
-            reactor,
+            reactor: ReactorRef::Borrowed(reactor),
+            batch,
+            handle: None,
+        }
+    }
+}
+
+impl<B: Batch> SubmitAndWait<'static, B> {
+    /// Create a future that submits onto the current reactor.
+    ///
+    /// # Panics
+    ///
+    /// If there's no current reactor.
+    pub fn with_current(batch: B) -> Self {
+        Self {
+            reactor: ReactorRef::Current(reactor::current()),
             batch,
             handle: None,
         }
@@ -34,7 +68,7 @@ where
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         let this = self.get_mut();
-        let mut reactor = this.reactor.borrow_mut();
+        let mut reactor = this.reactor.get().borrow_mut();
 
         let handle = *this
             .handle
@@ -54,7 +88,7 @@ where
 
 impl<B: Batch> Drop for SubmitAndWait<'_, B> {
     fn drop(&mut self) {
-        let mut reactor = self.reactor.borrow_mut();
+        let mut reactor = self.reactor.get().borrow_mut();
 
         if let Some(handle) = self.handle.take() {
             self.batch.drop_operations(handle, &mut reactor);
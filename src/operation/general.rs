@@ -1,6 +1,7 @@
 use std::{
     any::Any,
     io::{Error, Result},
+    os::fd::{AsRawFd as _, BorrowedFd},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -8,7 +9,7 @@ use io_uring::{
     cqueue,
     opcode,
     squeue,
-    types::{TimeoutFlags, Timespec},
+    types::{Fd, TimeoutFlags, Timespec},
 };
 
 use crate::operation::{Oneshot, Operation};
@@ -105,3 +106,43 @@ unsafe impl Operation for LinkTimeout {
 
 // SAFETY: only returns once
 unsafe impl Oneshot for LinkTimeout {}
+
+/// Operation that waits for a file to become ready for any of the specified
+/// events, returning the ones that are.
+///
+/// Corresponds to [io_uring_prep_poll_add(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_poll_add.3.html).
+#[must_use]
+pub struct PollAdd<'file> {
+    events: u32,
+    file: BorrowedFd<'file>,
+}
+
+impl<'file> PollAdd<'file> {
+    pub const fn new(file: BorrowedFd<'file>, events: u32) -> Self {
+        Self { events, file }
+    }
+}
+
+// SAFETY: no parameters to invalidate
+unsafe impl Operation for PollAdd<'_> {
+    type Output = Result<u32>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::PollAdd::new(Fd(self.file.as_raw_fd()), self.events).build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        Ok(entry.result().cast_unsigned())
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for PollAdd<'_> {}
@@ -6,7 +6,7 @@ use std::task::{Context, Poll};
 use io_uring::squeue::Flags;
 
 use crate::{
-    operation::{Batch, Oneshot, Operation as _, StashOutput},
+    operation::{Batch, Fallible, Oneshot, Operation as _, StashOutput},
     reactor::{OperationId, Reactor},
 };
 
@@ -52,7 +52,10 @@ macro_rules! define_link_structs {
             }
 
             // SAFETY: the safety requirements are identical
-            unsafe impl<$($generic_name: Oneshot),*> Batch for $struct_name<$($generic_name),*> {
+            unsafe impl<$($generic_name),*> Batch for $struct_name<$($generic_name),*>
+            where
+                $($generic_name: Oneshot, $generic_name::Output: Fallible,)*
+            {
                 type Handle = ($(replace_ident!($generic_name, OperationId),)*);
                 type Output = ($($generic_name::Output,)*);
 
@@ -71,14 +74,14 @@ macro_rules! define_link_structs {
                             // entry and add the link flag unless it's the last
                             // SAFETY: calling next only as many times as there are entries
                             let entry = unsafe { entries.next().unwrap_unchecked() };
-                            let entry = if entries.len() != 0 {
-                                entry.flags(Flags::IO_LINK)
+                            let link = if entries.len() != 0 {
+                                Flags::IO_LINK
                             } else {
-                                entry
+                                Flags::empty()
                             };
 
                             // SAFETY: operation implementations guarantee safety
-                            unsafe { reactor.queue_submission(entry, context) }
+                            unsafe { reactor.queue_linked_submission(entry, link, context) }
                         };
                     )*
 
@@ -94,9 +97,12 @@ macro_rules! define_link_structs {
                     $(
                         // go through and poll any unfinished operations, bailing out unless ready
                         if self.$field_name.not_finished() {
-                            let output = reactor.poll_completion($field_name, context).map(|entry| {
-                                // SAFETY: caller guarantees that we control the submission
-                                unsafe { self.$field_name.handle_completion(entry) }
+                            let output = reactor.poll_completion($field_name, context).map(|result| {
+                                match result {
+                                    // SAFETY: caller guarantees that we control the submission
+                                    Ok(entry) => unsafe { self.$field_name.handle_completion(entry) },
+                                    Err(error) => self.$field_name.fail(error),
+                                }
                             });
 
                             if output.is_pending() {
@@ -0,0 +1,147 @@
+use std::{
+    any::Any,
+    io::{Error, Result},
+    os::fd::{AsRawFd as _, BorrowedFd, OwnedFd},
+};
+
+use io_uring::{
+    cqueue,
+    opcode,
+    squeue,
+    types::{DestinationSlot, Fd, Fixed},
+};
+
+use crate::operation::{net::completion_descriptor, Oneshot, Operation};
+
+/// Operation that posts a completion with the specified data onto another
+/// ring.
+///
+/// Corresponds to [io_uring_prep_msg_ring(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_msg_ring.3.html).
+#[must_use]
+pub struct MsgRingData<'ring> {
+    result: i32,
+    ring: BorrowedFd<'ring>,
+    user_data: u64,
+}
+
+impl<'ring> MsgRingData<'ring> {
+    pub const fn new(ring: BorrowedFd<'ring>, result: i32, user_data: u64) -> Self {
+        Self {
+            result,
+            ring,
+            user_data,
+        }
+    }
+}
+
+// SAFETY: no parameters to invalidate
+unsafe impl Operation for MsgRingData<'_> {
+    type Output = Result<()>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::MsgRingData::new(Fd(self.ring.as_raw_fd()), self.result, self.user_data, None)
+            .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        Ok(())
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for MsgRingData<'_> {}
+
+/// Operation that installs a registered file into the first free registered
+/// file slot of another ring, which receives a completion with the slot as the
+/// result.
+///
+/// Corresponds to [io_uring_prep_msg_ring_fd_alloc(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_msg_ring_fd_alloc.3.html).
+#[must_use]
+pub struct MsgRingSendFd<'ring> {
+    ring: BorrowedFd<'ring>,
+    slot: u32,
+    user_data: u64,
+}
+
+impl<'ring> MsgRingSendFd<'ring> {
+    pub const fn new(ring: BorrowedFd<'ring>, slot: u32, user_data: u64) -> Self {
+        Self {
+            ring,
+            slot,
+            user_data,
+        }
+    }
+}
+
+// SAFETY: no parameters to invalidate
+unsafe impl Operation for MsgRingSendFd<'_> {
+    type Output = Result<()>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::MsgRingSendFd::new(
+            Fd(self.ring.as_raw_fd()),
+            Fixed(self.slot),
+            DestinationSlot::auto_target(),
+            self.user_data,
+        )
+        .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        Ok(())
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for MsgRingSendFd<'_> {}
+
+/// Operation that turns a registered file into a regular file descriptor.
+///
+/// Corresponds to [io_uring_prep_fixed_fd_install(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_fixed_fd_install.3.html).
+#[must_use]
+pub struct FixedFdInstall {
+    slot: u32,
+}
+
+impl FixedFdInstall {
+    pub const fn new(slot: u32) -> Self {
+        Self { slot }
+    }
+}
+
+// SAFETY: no parameters to invalidate
+unsafe impl Operation for FixedFdInstall {
+    type Output = Result<OwnedFd>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        // no flags means that the descriptor is created with close-on-exec
+        opcode::FixedFdInstall::new(Fixed(self.slot), 0).build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        completion_descriptor(&entry)
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for FixedFdInstall {}
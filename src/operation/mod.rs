@@ -1,18 +1,37 @@
 //! Primary abstraction around operations and some wrappers.
+mod address;
 mod definition;
 mod future;
 mod general;
 mod io;
 mod link;
+mod message;
+mod net;
 mod synchronization;
 mod wrapper;
 
 pub use self::{
-    definition::{Batch, Oneshot, Operation},
+    address::{RawAddress, SocketAddress},
+    definition::{Batch, Fallible, Oneshot, Operation},
     future::SubmitAndWait,
-    general::{LinkTimeout, Nop},
+    general::{LinkTimeout, Nop, PollAdd},
     io::{Read, Write},
     link::{Link2, Link3, Link4, Link5},
+    message::{FixedFdInstall, MsgRingData, MsgRingSendFd},
+    net::{
+        Accept,
+        Connect,
+        Recv,
+        RecvFds,
+        RecvMsg,
+        Send,
+        SendMsg,
+        SendMsgZc,
+        SendZc,
+        Shutdown,
+        Socket,
+        Truncated,
+    },
     synchronization::{FutexWait, FutexWake},
-    wrapper::{MapOutput, Single, StashOutput},
+    wrapper::{MapOutput, Multiple, Single, StashOutput},
 };
@@ -0,0 +1,924 @@
+use std::{
+    any::Any,
+    fmt::{self, Display, Formatter},
+    io::{Error, ErrorKind, Result},
+    marker::PhantomData,
+    net::{self, SocketAddr},
+    os::fd::{AsRawFd as _, BorrowedFd, FromRawFd as _, OwnedFd, RawFd},
+    task::{Context, Poll},
+};
+
+use io_uring::{cqueue, opcode, squeue, types::Fd};
+
+use crate::{
+    operation::{Batch, Oneshot, Operation, RawAddress, SocketAddress},
+    reactor::{OperationId, Reactor},
+};
+
+/// Heap allocated message header along with everything it points to, so that
+/// the owning operation can be moved around freely.
+struct MessageHeader {
+    address: RawAddress,
+    control: Vec<usize>,
+    message: libc::msghdr,
+    vector: libc::iovec,
+}
+
+impl MessageHeader {
+    /// Attach descriptors to be passed along with the data.
+    fn attach_descriptors(&mut self, descriptors: &[BorrowedFd]) {
+        let raw = descriptors
+            .iter()
+            .flat_map(|descriptor| descriptor.as_raw_fd().to_ne_bytes())
+            .collect::<Vec<_>>();
+
+        self.reserve_control(raw.len());
+
+        // SAFETY: all zeroes is a valid control message header
+        let mut header: libc::cmsghdr = unsafe { std::mem::zeroed() };
+        header.cmsg_level = libc::SOL_SOCKET;
+        header.cmsg_type = libc::SCM_RIGHTS;
+        header.cmsg_len = control_length(raw.len());
+
+        let start = self.control.as_mut_ptr().cast::<u8>();
+
+        // SAFETY: the control buffer is aligned and large enough for the header
+        unsafe { std::ptr::write(start.cast(), header) };
+
+        // SAFETY: the control buffer has space reserved for the payload
+        let data = unsafe { start.add(control_length(0)) };
+
+        // SAFETY: the control buffer has space reserved for the payload
+        unsafe { std::ptr::copy_nonoverlapping(raw.as_ptr(), data, raw.len()) };
+    }
+
+    fn new(data: *mut u8, length: usize, address: Option<RawAddress>) -> Box<Self> {
+        let named = address.is_some();
+        let mut header = Box::new(Self {
+            // SAFETY: all zeroes is a valid message header
+            message: unsafe { std::mem::zeroed() },
+            vector: libc::iovec {
+                iov_base: data.cast(),
+                iov_len: length,
+            },
+            address: address.unwrap_or_else(RawAddress::empty),
+            control: Vec::new(),
+        });
+
+        header.message.msg_iov = &raw mut header.vector;
+        header.message.msg_iovlen = 1;
+
+        if named {
+            header.message.msg_name = header.address.as_mut_ptr().cast();
+            header.message.msg_namelen = header.address.len();
+        }
+
+        header
+    }
+
+    /// Header describing the spare capacity of the buffer along with storage
+    /// for the sender's address.
+    fn receiving(buffer: &mut Vec<u8>) -> Box<Self> {
+        let spare = buffer.spare_capacity_mut();
+        Self::new(
+            spare.as_mut_ptr().cast(),
+            spare.len(),
+            Some(RawAddress::empty()),
+        )
+    }
+
+    /// Point the header at a control buffer that can hold the specified amount
+    /// of bytes as the payload of a single control message.
+    fn reserve_control(&mut self, payload: usize) -> usize {
+        let payload = payload.try_into().unwrap_or(u32::MAX);
+
+        // SAFETY: only computes a length
+        let space = unsafe { libc::CMSG_SPACE(payload) };
+        let space = usize::try_from(space).unwrap_or(usize::MAX);
+
+        self.control = vec![0; space.div_ceil(size_of::<usize>())];
+        self.message.msg_control = self.control.as_mut_ptr().cast();
+        self.message.msg_controllen = space;
+
+        space
+    }
+
+    /// Header describing the initialized part of the buffer.
+    fn sending(buffer: &mut Vec<u8>, address: Option<RawAddress>) -> Box<Self> {
+        Self::new(buffer.as_mut_ptr(), buffer.len(), address)
+    }
+
+    /// Collect the descriptors the kernel passed along with the data.
+    fn take_descriptors(&self) -> Vec<OwnedFd> {
+        let length = self.message.msg_controllen.min(size_of_val(&*self.control));
+        let bytes = self
+            .control
+            .iter()
+            .flat_map(|word| word.to_ne_bytes())
+            .take(length)
+            .collect::<Vec<_>>();
+
+        let mut descriptors = Vec::new();
+        let mut offset = 0;
+
+        while offset + control_length(0) <= bytes.len() {
+            // SAFETY: all zeroes is a valid control message header
+            let mut header: libc::cmsghdr = unsafe { std::mem::zeroed() };
+            let source = bytes[offset..].as_ptr();
+            let target = (&raw mut header).cast::<u8>();
+
+            // SAFETY: bounds checked above
+            unsafe { std::ptr::copy_nonoverlapping(source, target, size_of_val(&header)) };
+
+            let end = (offset + header.cmsg_len).min(bytes.len());
+            let data = bytes
+                .get(offset + control_length(0)..end)
+                .unwrap_or_default();
+
+            if header.cmsg_level == libc::SOL_SOCKET && header.cmsg_type == libc::SCM_RIGHTS {
+                for raw in data.chunks_exact(size_of::<RawFd>()) {
+                    let raw = RawFd::from_ne_bytes(raw.try_into().unwrap_or_default());
+
+                    // SAFETY: the kernel just installed the descriptor for us
+                    descriptors.push(unsafe { OwnedFd::from_raw_fd(raw) });
+                }
+            }
+
+            if header.cmsg_len == 0 {
+                break;
+            }
+
+            offset += header.cmsg_len.next_multiple_of(size_of::<usize>());
+        }
+
+        descriptors
+    }
+}
+
+/// State tracking for zero-copy sends where the kernel produces a separate
+/// notification once it's done referencing the buffer.
+#[derive(Default)]
+struct Notified {
+    sent: Option<Result<usize>>,
+}
+
+impl Notified {
+    /// Handle a completion, returning the final result once the buffer is no
+    /// longer in use by the kernel.
+    fn handle_completion(&mut self, entry: &cqueue::Entry) -> Option<Result<usize>> {
+        if cqueue::notif(entry.flags()) {
+            return Some(self.sent.take().unwrap_or(Ok(0)));
+        }
+
+        let sent = if entry.result().is_negative() {
+            Err(Error::from_raw_os_error(-entry.result()))
+        } else {
+            Ok(entry.result().try_into().unwrap_or(usize::MAX))
+        };
+
+        // a notification only follows if the kernel says so
+        if !cqueue::more(entry.flags()) {
+            return Some(sent);
+        }
+
+        self.sent = Some(sent);
+        None
+    }
+}
+
+/// Operation that sends data from a socket without copying the buffer.
+///
+/// Corresponds to [io_uring_prep_send_zc(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_send_zc.3.html).
+///
+/// The buffer is only handed back once the kernel has sent the notification
+/// that it no longer references it, so it can't get reused too early. It's
+/// handed back along with the result, so it isn't lost when sending fails.
+#[must_use]
+pub struct SendZc<'socket> {
+    buffer: Vec<u8>,
+    socket: BorrowedFd<'socket>,
+    state: Notified,
+}
+
+impl<'socket> SendZc<'socket> {
+    /// Send the whole buffer to the address the socket is connected to.
+    pub fn new(socket: BorrowedFd<'socket>, buffer: Vec<u8>) -> Self {
+        Self {
+            socket,
+            buffer,
+            state: Notified::default(),
+        }
+    }
+}
+
+// SAFETY: the buffer is stashed away until the notification arrives
+unsafe impl Batch for SendZc<'_> {
+    type Handle = OperationId;
+    type Output = (Result<usize>, Vec<u8>);
+
+    fn drop_operations(&mut self, handle: Self::Handle, reactor: &mut Reactor) {
+        reactor.ignore_operation(handle, Some(Box::new(std::mem::take(&mut self.buffer))));
+    }
+
+    unsafe fn poll_progress(
+        &mut self,
+        handle: Self::Handle,
+        reactor: &mut Reactor,
+        context: &Context,
+    ) -> Poll<Self::Output> {
+        loop {
+            let sent = match std::task::ready!(reactor.poll_completion(handle, context)) {
+                Ok(entry) => self.state.handle_completion(&entry),
+                Err(error) => Some(Err(error)),
+            };
+
+            if let Some(sent) = sent {
+                return Poll::Ready((sent, std::mem::take(&mut self.buffer)));
+            }
+        }
+    }
+
+    fn submit_entries(&mut self, reactor: &mut Reactor, context: Option<&Context>) -> Self::Handle {
+        let entry = opcode::SendZc::new(
+            Fd(self.socket.as_raw_fd()),
+            self.buffer.as_ptr(),
+            self.buffer.len().try_into().unwrap_or(u32::MAX),
+        )
+        .build();
+
+        // SAFETY: the buffer is kept alive through the drop implementation
+        unsafe { reactor.queue_submission(entry, context) }
+    }
+}
+
+/// Operation that sends a message from a socket without copying the buffer.
+///
+/// Corresponds to [io_uring_prep_sendmsg_zc(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_sendmsg_zc.3.html).
+///
+/// Like with [`SendZc`], the buffer is only handed back after the notification.
+#[must_use]
+pub struct SendMsgZc<'socket> {
+    buffer: Vec<u8>,
+    header: Option<Box<MessageHeader>>,
+    socket: BorrowedFd<'socket>,
+    state: Notified,
+}
+
+impl<'socket> SendMsgZc<'socket> {
+    /// Send to the address the socket is connected to.
+    pub fn new(socket: BorrowedFd<'socket>, mut buffer: Vec<u8>) -> Self {
+        Self {
+            socket,
+            header: Some(MessageHeader::sending(&mut buffer, None)),
+            buffer,
+            state: Notified::default(),
+        }
+    }
+
+    /// Send to an explicitly specified address.
+    pub fn to<A: SocketAddress>(
+        socket: BorrowedFd<'socket>,
+        mut buffer: Vec<u8>,
+        address: &A,
+    ) -> Self {
+        Self {
+            socket,
+            header: Some(MessageHeader::sending(&mut buffer, Some(address.encode()))),
+            buffer,
+            state: Notified::default(),
+        }
+    }
+}
+
+// SAFETY: the buffer and header are stashed away until the notification arrives
+unsafe impl Batch for SendMsgZc<'_> {
+    type Handle = OperationId;
+    type Output = (Result<usize>, Vec<u8>);
+
+    fn drop_operations(&mut self, handle: Self::Handle, reactor: &mut Reactor) {
+        reactor.ignore_operation(
+            handle,
+            Some(Box::new((
+                std::mem::take(&mut self.buffer),
+                self.header.take(),
+            ))),
+        );
+    }
+
+    unsafe fn poll_progress(
+        &mut self,
+        handle: Self::Handle,
+        reactor: &mut Reactor,
+        context: &Context,
+    ) -> Poll<Self::Output> {
+        loop {
+            let sent = match std::task::ready!(reactor.poll_completion(handle, context)) {
+                Ok(entry) => self.state.handle_completion(&entry),
+                Err(error) => Some(Err(error)),
+            };
+
+            if let Some(sent) = sent {
+                return Poll::Ready((sent, std::mem::take(&mut self.buffer)));
+            }
+        }
+    }
+
+    fn submit_entries(&mut self, reactor: &mut Reactor, context: Option<&Context>) -> Self::Handle {
+        let header = self
+            .header
+            .as_ref()
+            .expect("operation shouldn't be submitted again after being dropped");
+
+        let entry =
+            opcode::SendMsgZc::new(Fd(self.socket.as_raw_fd()), &raw const header.message).build();
+
+        // SAFETY: the buffer and header are kept alive through the drop implementation
+        unsafe { reactor.queue_submission(entry, context) }
+    }
+}
+
+/// Operation that creates a new socket.
+///
+/// Corresponds to [io_uring_prep_socket(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_socket.3.html).
+#[must_use]
+pub struct Socket {
+    domain: i32,
+    kind: i32,
+    protocol: i32,
+}
+
+impl Socket {
+    /// Create a socket with the close-on-exec flag set.
+    pub const fn new(domain: i32, kind: i32, protocol: i32) -> Self {
+        Self {
+            domain,
+            kind: kind | libc::SOCK_CLOEXEC,
+            protocol,
+        }
+    }
+
+    /// Create a stream socket matching the address family.
+    pub const fn stream_for(address: &SocketAddr) -> Self {
+        let domain = match address {
+            SocketAddr::V4(_) => libc::AF_INET,
+            SocketAddr::V6(_) => libc::AF_INET6,
+        };
+
+        Self::new(domain, libc::SOCK_STREAM, 0)
+    }
+}
+
+// SAFETY: no parameters to invalidate
+unsafe impl Operation for Socket {
+    type Output = Result<OwnedFd>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::Socket::new(self.domain, self.kind, self.protocol).build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        completion_descriptor(&entry)
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for Socket {}
+
+/// Operation that accepts a connection on a listening socket.
+///
+/// Corresponds to [io_uring_prep_accept(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_accept.3.html).
+#[must_use]
+pub struct Accept<'socket> {
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket> Accept<'socket> {
+    pub const fn new(socket: BorrowedFd<'socket>) -> Self {
+        Self { socket }
+    }
+}
+
+// SAFETY: no parameters to invalidate
+unsafe impl Operation for Accept<'_> {
+    type Output = Result<OwnedFd>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::Accept::new(
+            Fd(self.socket.as_raw_fd()),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+        .flags(libc::SOCK_CLOEXEC)
+        .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        completion_descriptor(&entry)
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for Accept<'_> {}
+
+/// Operation that connects a socket to an address.
+///
+/// Corresponds to [io_uring_prep_connect(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_connect.3.html).
+#[must_use]
+pub struct Connect<'socket> {
+    address: Box<RawAddress>,
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket> Connect<'socket> {
+    pub fn new<A: SocketAddress>(socket: BorrowedFd<'socket>, address: &A) -> Self {
+        Self {
+            socket,
+            address: Box::new(address.encode()),
+        }
+    }
+}
+
+// SAFETY: the address is copied by the kernel during submission
+unsafe impl Operation for Connect<'_> {
+    type Output = Result<()>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::Connect::new(
+            Fd(self.socket.as_raw_fd()),
+            self.address.as_ptr(),
+            self.address.len(),
+        )
+        .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        Ok(())
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for Connect<'_> {}
+
+/// Operation that receives data from a socket.
+///
+/// Corresponds to [io_uring_prep_recv(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_recv.3.html).
+#[must_use]
+pub struct Recv<'socket> {
+    buffer: Vec<u8>,
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket> Recv<'socket> {
+    pub const fn new(socket: BorrowedFd<'socket>, buffer: Vec<u8>) -> Self {
+        Self { buffer, socket }
+    }
+}
+
+// SAFETY: the buffer is stashed away when dropped
+unsafe impl Operation for Recv<'_> {
+    type Output = Result<Vec<u8>>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        let spare = self.buffer.spare_capacity_mut();
+
+        opcode::Recv::new(
+            Fd(self.socket.as_raw_fd()),
+            spare.as_mut_ptr().cast(),
+            spare.len().try_into().unwrap_or(u32::MAX),
+        )
+        .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        // SAFETY: we have to trust the kernel
+        unsafe {
+            let amount = entry.result().try_into().unwrap_or(usize::MAX);
+            self.buffer.set_len(self.buffer.len() + amount);
+        }
+
+        Ok(std::mem::take(&mut self.buffer))
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new(std::mem::take(&mut self.buffer)))
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for Recv<'_> {}
+
+/// Operation that sends data from a socket.
+///
+/// Corresponds to [io_uring_prep_send(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_send.3.html).
+///
+/// Unlike [`Write`](crate::operation::Write), this takes ownership of the
+/// buffer and hands it back once the operation has completed.
+#[must_use]
+pub struct Send<'socket> {
+    buffer: Vec<u8>,
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket> Send<'socket> {
+    pub const fn new(socket: BorrowedFd<'socket>, buffer: Vec<u8>) -> Self {
+        Self { buffer, socket }
+    }
+}
+
+// SAFETY: the buffer is stashed away when dropped
+unsafe impl Operation for Send<'_> {
+    type Output = Result<(usize, Vec<u8>)>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::Send::new(
+            Fd(self.socket.as_raw_fd()),
+            self.buffer.as_ptr(),
+            self.buffer.len().try_into().unwrap_or(u32::MAX),
+        )
+        .flags(libc::MSG_NOSIGNAL)
+        .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        let amount = entry.result().try_into().unwrap_or(usize::MAX);
+        Ok((amount, std::mem::take(&mut self.buffer)))
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new(std::mem::take(&mut self.buffer)))
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for Send<'_> {}
+
+/// Operation that shuts down parts of a full-duplex connection.
+///
+/// Corresponds to [io_uring_prep_shutdown(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_shutdown.3.html).
+#[must_use]
+pub struct Shutdown<'socket> {
+    how: i32,
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket> Shutdown<'socket> {
+    pub const fn new(socket: BorrowedFd<'socket>, how: net::Shutdown) -> Self {
+        let how = match how {
+            net::Shutdown::Read => libc::SHUT_RD,
+            net::Shutdown::Write => libc::SHUT_WR,
+            net::Shutdown::Both => libc::SHUT_RDWR,
+        };
+
+        Self { how, socket }
+    }
+}
+
+// SAFETY: no parameters to invalidate
+unsafe impl Operation for Shutdown<'_> {
+    type Output = Result<()>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        opcode::Shutdown::new(Fd(self.socket.as_raw_fd()), self.how).build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        Ok(())
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for Shutdown<'_> {}
+
+/// Operation that sends a message from a socket.
+///
+/// Corresponds to [io_uring_prep_sendmsg(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_sendmsg.3.html).
+#[must_use]
+pub struct SendMsg<'socket> {
+    buffer: Vec<u8>,
+    header: Option<Box<MessageHeader>>,
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket> SendMsg<'socket> {
+    /// Send to the address the socket is connected to.
+    pub fn new(socket: BorrowedFd<'socket>, mut buffer: Vec<u8>) -> Self {
+        Self {
+            socket,
+            header: Some(MessageHeader::sending(&mut buffer, None)),
+            buffer,
+        }
+    }
+
+    /// Send to an explicitly specified address.
+    pub fn to<A: SocketAddress>(
+        socket: BorrowedFd<'socket>,
+        mut buffer: Vec<u8>,
+        address: &A,
+    ) -> Self {
+        Self {
+            socket,
+            header: Some(MessageHeader::sending(&mut buffer, Some(address.encode()))),
+            buffer,
+        }
+    }
+
+    /// Pass the descriptors along with the data.
+    ///
+    /// This only works with unix sockets, and stream sockets also require at
+    /// least one byte of actual data to be sent.
+    pub fn with_fds(mut self, descriptors: &[BorrowedFd]) -> Self {
+        if let Some(header) = &mut self.header {
+            header.attach_descriptors(descriptors);
+        }
+
+        self
+    }
+}
+
+// SAFETY: the buffer and header are stashed away when dropped
+unsafe impl Operation for SendMsg<'_> {
+    type Output = Result<(usize, Vec<u8>)>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        let header = self
+            .header
+            .as_ref()
+            .expect("operation shouldn't be submitted again after being dropped");
+
+        opcode::SendMsg::new(Fd(self.socket.as_raw_fd()), &raw const header.message)
+            .flags(libc::MSG_NOSIGNAL.cast_unsigned())
+            .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        let amount = entry.result().try_into().unwrap_or(usize::MAX);
+        Ok((amount, std::mem::take(&mut self.buffer)))
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new((
+            std::mem::take(&mut self.buffer),
+            self.header.take(),
+        )))
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for SendMsg<'_> {}
+
+/// Part of a received message that didn't fit into the provided buffers and
+/// got discarded by the kernel, which is surfaced as the inner error of an
+/// [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Truncated {
+    /// More descriptors were passed than there was space for, with the kernel
+    /// closing the excess ones.
+    Control,
+    /// The datagram was larger than the buffer.
+    Data,
+}
+
+impl Truncated {
+    /// Get the truncation behind an error, if that's what caused it.
+    #[must_use]
+    pub fn from_error(error: &Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl Display for Truncated {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Data => f.write_str("message was truncated to fit the buffer"),
+            Self::Control => f.write_str("passed descriptors didn't fit the control buffer"),
+        }
+    }
+}
+
+impl std::error::Error for Truncated {}
+
+impl From<Truncated> for Error {
+    fn from(truncated: Truncated) -> Self {
+        Self::new(ErrorKind::InvalidData, truncated)
+    }
+}
+
+/// Operation that receives a message from a socket along with the sender's
+/// address.
+///
+/// Corresponds to [io_uring_prep_recvmsg(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_recvmsg.3.html).
+#[must_use]
+pub struct RecvMsg<'socket, A> {
+    address: PhantomData<fn() -> A>,
+    buffer: Vec<u8>,
+    flags: u32,
+    header: Option<Box<MessageHeader>>,
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket, A> RecvMsg<'socket, A> {
+    /// Receive into the spare capacity of the buffer.
+    pub fn new(socket: BorrowedFd<'socket>, mut buffer: Vec<u8>) -> Self {
+        Self {
+            socket,
+            header: Some(MessageHeader::receiving(&mut buffer)),
+            buffer,
+            flags: 0,
+            address: PhantomData,
+        }
+    }
+
+    /// Fail with [`ErrorKind::WouldBlock`] instead of waiting when there's no
+    /// message queued up already.
+    pub const fn nonblocking(mut self) -> Self {
+        self.flags |= libc::MSG_DONTWAIT.cast_unsigned();
+        self
+    }
+}
+
+// SAFETY: the buffer and header are stashed away when dropped
+unsafe impl<A: SocketAddress> Operation for RecvMsg<'_, A> {
+    type Output = Result<(Vec<u8>, A)>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        let header = self
+            .header
+            .as_mut()
+            .expect("operation shouldn't be submitted again after being dropped");
+
+        opcode::RecvMsg::new(Fd(self.socket.as_raw_fd()), &raw mut header.message)
+            .flags(self.flags)
+            .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        let Some(mut header) = self.header.take() else {
+            unreachable!();
+        };
+
+        // SAFETY: we have to trust the kernel
+        unsafe {
+            let amount = entry.result().try_into().unwrap_or(usize::MAX);
+            self.buffer.set_len(self.buffer.len() + amount);
+        }
+
+        if header.message.msg_flags & libc::MSG_TRUNC != 0 {
+            return Err(Truncated::Data.into());
+        }
+
+        let length = header.message.msg_namelen;
+        header.address.set_len(length);
+
+        let address = A::decode(&header.address)?;
+        Ok((std::mem::take(&mut self.buffer), address))
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new((
+            std::mem::take(&mut self.buffer),
+            self.header.take(),
+        )))
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl<A: SocketAddress> Oneshot for RecvMsg<'_, A> {}
+
+/// Operation that receives data from a unix socket along with any descriptors
+/// passed with it.
+///
+/// Corresponds to [io_uring_prep_recvmsg(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_recvmsg.3.html).
+#[must_use]
+pub struct RecvFds<'socket> {
+    buffer: Vec<u8>,
+    header: Option<Box<MessageHeader>>,
+    socket: BorrowedFd<'socket>,
+}
+
+impl<'socket> RecvFds<'socket> {
+    /// Receive into the spare capacity of the buffer, accepting up to the
+    /// specified amount of descriptors.
+    pub fn new(socket: BorrowedFd<'socket>, mut buffer: Vec<u8>, descriptors: usize) -> Self {
+        let mut header = MessageHeader::receiving(&mut buffer);
+        header.reserve_control(descriptors * size_of::<RawFd>());
+
+        Self {
+            socket,
+            buffer,
+            header: Some(header),
+        }
+    }
+}
+
+// SAFETY: the buffer and header are stashed away when dropped
+unsafe impl Operation for RecvFds<'_> {
+    type Output = Result<(Vec<u8>, Vec<OwnedFd>)>;
+
+    fn build_submission(&mut self) -> squeue::Entry {
+        let header = self
+            .header
+            .as_mut()
+            .expect("operation shouldn't be submitted again after being dropped");
+
+        opcode::RecvMsg::new(Fd(self.socket.as_raw_fd()), &raw mut header.message)
+            .flags(libc::MSG_CMSG_CLOEXEC.cast_unsigned())
+            .build()
+    }
+
+    unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
+        let Some(header) = self.header.take() else {
+            unreachable!();
+        };
+
+        // descriptors have been installed even if the message got truncated
+        let descriptors = header.take_descriptors();
+
+        if entry.result().is_negative() {
+            return Err(Error::from_raw_os_error(-entry.result()));
+        }
+
+        // SAFETY: we have to trust the kernel
+        unsafe {
+            let amount = entry.result().try_into().unwrap_or(usize::MAX);
+            self.buffer.set_len(self.buffer.len() + amount);
+        }
+
+        // the descriptors that did fit are closed along with the error, as the
+        // message is incomplete either way
+        if header.message.msg_flags & libc::MSG_CTRUNC != 0 {
+            return Err(Truncated::Control.into());
+        }
+
+        Ok((std::mem::take(&mut self.buffer), descriptors))
+    }
+
+    fn take_required_allocations(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new((
+            std::mem::take(&mut self.buffer),
+            self.header.take(),
+        )))
+    }
+}
+
+// SAFETY: only returns once
+unsafe impl Oneshot for RecvFds<'_> {}
+
+/// Length of a control message with the specified payload size.
+fn control_length(payload: usize) -> usize {
+    let payload = payload.try_into().unwrap_or(u32::MAX);
+
+    // SAFETY: only computes a length
+    let length = unsafe { libc::CMSG_LEN(payload) };
+    usize::try_from(length).unwrap_or(usize::MAX)
+}
+
+/// Convert a completion result into an owned file descriptor.
+pub(super) fn completion_descriptor(entry: &cqueue::Entry) -> Result<OwnedFd> {
+    if entry.result().is_negative() {
+        return Err(Error::from_raw_os_error(-entry.result()));
+    }
+
+    // SAFETY: the kernel just handed us a fresh descriptor
+    Ok(unsafe { OwnedFd::from_raw_fd(entry.result()) })
+}
@@ -8,6 +8,13 @@ use io_uring::{cqueue, opcode, squeue};
 
 use crate::operation::{Oneshot, Operation};
 
+/// Futex flags for process private 32-bit futexes, which are compatible with
+/// the private variants of the regular futex syscall.
+const FLAGS: u32 = libc::FUTEX2_SIZE_U32.unsigned_abs() | libc::FUTEX2_PRIVATE.unsigned_abs();
+
+/// Bitset that matches every waiter, as an empty one is rejected.
+const MATCH_ANY: u64 = 0xffff_ffff;
+
 /// Invoke a futex wait request.
 ///
 /// Corresponds to [io_uring_prep_futex_wait(3)](https://www.man7.org/linux/man-pages/man3/io_uring_prep_futex_wait.3.html).
@@ -28,7 +35,13 @@ unsafe impl Operation for FutexWait<'_> {
     type Output = Result<()>;
 
     fn build_submission(&mut self) -> squeue::Entry {
-        opcode::FutexWait::new(self.futex.as_ptr().cast_const(), self.compare.into(), 0, 0).build()
+        opcode::FutexWait::new(
+            self.futex.as_ptr().cast_const(),
+            self.compare.into(),
+            MATCH_ANY,
+            FLAGS,
+        )
+        .build()
     }
 
     unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
@@ -67,7 +80,13 @@ unsafe impl Operation for FutexWake<'_> {
     type Output = Result<()>;
 
     fn build_submission(&mut self) -> squeue::Entry {
-        opcode::FutexWake::new(self.futex.as_ptr().cast_const(), self.count.into(), 0, 0).build()
+        opcode::FutexWake::new(
+            self.futex.as_ptr().cast_const(),
+            self.count.into(),
+            MATCH_ANY,
+            FLAGS,
+        )
+        .build()
     }
 
     unsafe fn handle_completion(&mut self, entry: cqueue::Entry) -> Self::Output {
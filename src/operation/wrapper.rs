@@ -1,12 +1,13 @@
 use std::{
     any::Any,
+    io::Error,
     task::{Context, Poll},
 };
 
 use io_uring::{cqueue, squeue};
 
 use crate::{
-    operation::{Batch, Oneshot, Operation},
+    operation::{Batch, Fallible, Oneshot, Operation},
     reactor::{OperationId, Reactor},
 };
 
@@ -36,6 +37,24 @@ impl<O: Oneshot> StashOutput<O> {
     }
 }
 
+impl<O> StashOutput<O>
+where
+    O: Oneshot,
+    O::Output: Fallible,
+{
+    /// Stash the output for the operation having failed without ever being
+    /// submitted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the output was already stashed.
+    pub fn fail(&mut self, error: Error) {
+        assert!(self.output.is_none());
+
+        self.output = Some(O::Output::from_failure(error));
+    }
+}
+
 // SAFETY: the internal operation promises safety
 unsafe impl<O: Oneshot> Operation for StashOutput<O> {
     type Output = ();
@@ -153,7 +172,11 @@ impl<O> Single<O> {
 }
 
 // SAFETY: the safety requirements are identical
-unsafe impl<O: Oneshot> Batch for Single<O> {
+unsafe impl<O> Batch for Single<O>
+where
+    O: Oneshot,
+    O::Output: Fallible,
+{
     type Handle = OperationId;
     type Output = O::Output;
 
@@ -168,13 +191,107 @@ unsafe impl<O: Oneshot> Batch for Single<O> {
         reactor: &mut Reactor,
         context: &Context,
     ) -> Poll<Self::Output> {
-        reactor.poll_completion(handle, context).map(|entry| {
-            // SAFETY: caller guarantees that we control the submission
-            unsafe { self.inner.handle_completion(entry) }
-        })
+        reactor
+            .poll_completion(handle, context)
+            .map(|result| match result {
+                // SAFETY: caller guarantees that we control the submission
+                Ok(entry) => unsafe { self.inner.handle_completion(entry) },
+                Err(error) => O::Output::from_failure(error),
+            })
     }
 
     fn drop_operations(&mut self, handle: Self::Handle, reactor: &mut Reactor) {
         reactor.ignore_operation(handle, self.inner.take_required_allocations());
     }
 }
+
+/// Any number of independent [`Oneshot`] operations acting as a [`Batch`].
+///
+/// As the amount of operations is only known at runtime, the submitted
+/// identifiers are kept internally instead of inside the handle.
+///
+/// Operations with opcodes the kernel doesn't support fail individually
+/// without holding back the rest.
+#[must_use]
+pub struct Multiple<O: Oneshot> {
+    operations: Vec<StashOutput<O>>,
+    submitted: Vec<OperationId>,
+}
+
+impl<O: Oneshot> Multiple<O> {
+    pub fn new<I: IntoIterator<Item = O>>(operations: I) -> Self {
+        Self {
+            operations: operations.into_iter().map(StashOutput::new).collect(),
+            submitted: Vec::new(),
+        }
+    }
+}
+
+// SAFETY: the safety requirements are identical
+unsafe impl<O> Batch for Multiple<O>
+where
+    O: Oneshot,
+    O::Output: Fallible,
+{
+    type Handle = ();
+    type Output = Vec<O::Output>;
+
+    fn drop_operations(&mut self, (): Self::Handle, reactor: &mut Reactor) {
+        for (operation, id) in self.operations.iter_mut().zip(self.submitted.drain(..)) {
+            // finished operations have already been cleaned up by the reactor
+            if operation.not_finished() {
+                reactor.ignore_operation(id, operation.take_required_allocations());
+            }
+        }
+    }
+
+    unsafe fn poll_progress(
+        &mut self,
+        (): Self::Handle,
+        reactor: &mut Reactor,
+        context: &Context,
+    ) -> Poll<Self::Output> {
+        let mut finished = true;
+
+        for (operation, id) in self.operations.iter_mut().zip(&self.submitted) {
+            if operation.not_finished() {
+                let output = reactor
+                    .poll_completion(*id, context)
+                    .map(|result| match result {
+                        // SAFETY: caller guarantees that we control the submission
+                        Ok(entry) => unsafe { operation.handle_completion(entry) },
+                        Err(error) => operation.fail(error),
+                    });
+
+                finished &= output.is_ready();
+            }
+        }
+
+        if !finished {
+            return Poll::Pending;
+        }
+
+        self.submitted.clear();
+
+        Poll::Ready(
+            self.operations
+                .iter_mut()
+                .map(|operation| {
+                    // SAFETY: every operation has finished at this point
+                    unsafe { operation.take_output().unwrap_unchecked() }
+                })
+                .collect(),
+        )
+    }
+
+    fn submit_entries(&mut self, reactor: &mut Reactor, context: Option<&Context>) -> Self::Handle {
+        self.submitted = self
+            .operations
+            .iter_mut()
+            .map(|operation| {
+                // SAFETY: operation implementations guarantee safety
+                unsafe { reactor.queue_submission(operation.build_submission(), context) }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,286 @@
+//! Counters and snapshots for figuring out what the reactor is up to.
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+
+use crate::reactor::OperationId;
+
+/// Snapshot of the state of a reactor along with counters accumulated over
+/// its lifetime.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Stats {
+    /// Operations with multiple completions that haven't been polled yet.
+    pub buffering: usize,
+    /// Operations with a completion that hasn't been polled yet.
+    pub completed: usize,
+    /// Completions reaped from the completion queue.
+    pub completions: u64,
+    /// Calls to `io_uring_enter`.
+    pub enters: u64,
+    /// Operations that failed without being submitted and haven't been polled
+    /// yet.
+    pub failed: usize,
+    /// Operations nobody is waiting for, including unsubmitted ones.
+    pub ignored: usize,
+    /// Counters for tracked operations by their opcode.
+    pub opcodes: BTreeMap<u8, OpcodeStats>,
+    /// Times completions have overflowed the completion queue and had to be
+    /// flushed separately.
+    pub overflows: u64,
+    /// Entries pushed onto the submission queue.
+    pub submissions: u64,
+    /// Completions that didn't correspond to any tracked operation or known
+    /// internal use.
+    pub unknown_completions: u64,
+    /// Times an operation that's no longer tracked has been polled or ignored.
+    pub unknown_operations: u64,
+    /// Entries queued up for the next submission.
+    pub unsubmitted: usize,
+    /// Operations waiting for a completion, including unsubmitted ones.
+    pub waiting: usize,
+}
+
+impl Stats {
+    pub(super) fn opcode(&mut self, opcode: u8) -> &mut OpcodeStats {
+        self.opcodes.entry(opcode).or_default()
+    }
+}
+
+/// Counters for tracked operations with a specific opcode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OpcodeStats {
+    /// Completions reaped from the completion queue.
+    pub completions: u64,
+    /// Completions with a negative result.
+    pub errors: u64,
+    /// Entries pushed onto the submission queue.
+    pub submissions: u64,
+}
+
+/// State of an operation as reported by [`Reactor::dump_operations`].
+///
+/// [`Reactor::dump_operations`]: crate::reactor::Reactor::dump_operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationStatus {
+    Buffering,
+    Completed,
+    Failed,
+    Ignored,
+    Unsubmitted,
+    Waiting,
+}
+
+/// Description of an operation that's still tracked by the reactor.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct OperationInfo {
+    /// Time since the operation was queued.
+    pub age: Duration,
+    pub id: OperationId,
+    pub opcode: u8,
+    pub status: OperationStatus,
+}
+
+/// Log-linear histogram of durations in the style of HDR histograms, with a
+/// relative precision of about 6% across the entire range.
+///
+/// Values are stored in nanoseconds, with every power of two split into
+/// [`Histogram::SUB_BUCKETS`] linear buckets.
+#[derive(Clone)]
+#[must_use]
+pub struct Histogram {
+    buckets: Box<[u64]>,
+    count: u64,
+    max: u64,
+    min: u64,
+    sum: u128,
+}
+
+impl Histogram {
+    /// Exact buckets for the smallest values followed by a set of linear ones
+    /// for every remaining power of two.
+    const BUCKETS: u32 = (u64::BITS - Self::SUB_BUCKET_BITS + 1) << Self::SUB_BUCKET_BITS;
+    /// Number of linear buckets in every power of two.
+    pub const SUB_BUCKETS: u64 = 1 << Self::SUB_BUCKET_BITS;
+    const SUB_BUCKET_BITS: u32 = 4;
+
+    fn bucket(value: u64) -> u64 {
+        if value < Self::SUB_BUCKETS {
+            return value;
+        }
+
+        let shift = u64::BITS - 1 - value.leading_zeros() - Self::SUB_BUCKET_BITS;
+        let sub_bucket = (value >> shift) - Self::SUB_BUCKETS;
+
+        (u64::from(shift) + 1) * Self::SUB_BUCKETS + sub_bucket
+    }
+
+    /// Iterate over the upper bounds and counts of buckets that have recorded
+    /// durations, such as for exporting them elsewhere.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        (0..)
+            .zip(self.buckets.iter())
+            .filter_map(|(bucket, &count)| {
+                // nothing gets recorded past the largest value, so the bounds of
+                // its bucket would be misleading
+                let upper = Self::upper_bound(bucket).min(self.max);
+                (count > 0).then(|| (Duration::from_nanos(upper), count))
+            })
+    }
+
+    /// Number of recorded durations.
+    #[must_use]
+    pub const fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Largest recorded duration.
+    #[must_use]
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos(self.max))
+    }
+
+    /// Average of the recorded durations.
+    #[must_use]
+    pub fn mean(&self) -> Option<Duration> {
+        let mean = self.sum.checked_div(self.count.into())?;
+        Some(Duration::from_nanos(mean.try_into().unwrap_or(u64::MAX)))
+    }
+
+    /// Smallest recorded duration.
+    #[must_use]
+    pub fn min(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos(self.min))
+    }
+
+    pub fn new() -> Self {
+        Self {
+            buckets: vec![0; usize::try_from(Self::BUCKETS).unwrap_or(usize::MAX)].into(),
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Get the duration that the specified fraction of recorded durations
+    /// don't exceed, such as `0.99` for the 99th percentile.
+    ///
+    /// The result is the upper bound of the bucket the duration falls into.
+    #[must_use]
+    #[expect(clippy::as_conversions, clippy::cast_precision_loss)]
+    pub fn quantile(&self, quantile: f64) -> Option<Duration> {
+        let target = quantile.clamp(0.0, 1.0) * self.count as f64;
+        let mut seen = 0;
+
+        self.buckets().find_map(|(upper, count)| {
+            seen += count;
+            (seen as f64 >= target).then_some(upper)
+        })
+    }
+
+    /// Record a single duration, saturating at roughly 584 years.
+    pub fn record(&mut self, duration: Duration) {
+        let value = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+
+        if let Some(bucket) = usize::try_from(Self::bucket(value))
+            .ok()
+            .and_then(|bucket| self.buckets.get_mut(bucket))
+        {
+            *bucket += 1;
+        }
+
+        self.count += 1;
+        self.sum += u128::from(value);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Clear all recorded durations.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    const fn upper_bound(bucket: u64) -> u64 {
+        if bucket < Self::SUB_BUCKETS {
+            return bucket;
+        }
+
+        let shift = bucket / Self::SUB_BUCKETS - 1;
+        let lower = (Self::SUB_BUCKETS + bucket % Self::SUB_BUCKETS) << shift;
+
+        lower + ((1 << shift) - 1)
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Histogram {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count)
+            .field("min", &self.min())
+            .field("mean", &self.mean())
+            .field("p50", &self.quantile(0.5))
+            .field("p99", &self.quantile(0.99))
+            .field("max", &self.max())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Histogram;
+
+    #[test]
+    fn buckets_contain_their_upper_bounds() {
+        let values = (0..u64::BITS)
+            .flat_map(|shift| [1 << shift, (1 << shift) + 1, (1 << shift) - 1])
+            .chain([u64::MAX, 1000, 123_456_789]);
+
+        for value in values {
+            let bucket = Histogram::bucket(value);
+            let upper = Histogram::upper_bound(bucket);
+
+            assert!(value <= upper, "{value} exceeds the bound of its bucket");
+            assert_eq!(Histogram::bucket(upper), bucket);
+
+            if let Some(next) = upper.checked_add(1) {
+                assert_eq!(Histogram::bucket(next), bucket + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn quantiles_stay_within_precision() {
+        let mut histogram = Histogram::new();
+
+        for micros in 1..=100 {
+            histogram.record(Duration::from_micros(micros));
+        }
+
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.min(), Some(Duration::from_micros(1)));
+        assert_eq!(histogram.max(), Some(Duration::from_micros(100)));
+
+        for (quantile, micros) in [(0.5, 50), (0.99, 99), (1.0, 100)] {
+            let exact = Duration::from_micros(micros);
+            let estimate = histogram.quantile(quantile).unwrap_or_default();
+
+            assert!(estimate >= exact && estimate <= exact + exact / 16);
+        }
+
+        histogram.reset();
+        assert_eq!(histogram.quantile(0.5), None);
+    }
+}
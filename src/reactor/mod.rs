@@ -0,0 +1,1377 @@
+//! Bits for submitting tasks and polling for their completion or blocking until
+//! there's progress available as needed.
+mod introspection;
+mod trace;
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    fmt::{self, Display, Formatter},
+    io::{Error, ErrorKind, Result},
+    os::fd::{AsFd, AsRawFd as _, BorrowedFd, FromRawFd as _, OwnedFd},
+    rc::Rc,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use fnv::FnvBuildHasher;
+use indexmap::IndexMap;
+use io_uring::{
+    cqueue,
+    opcode::PollAdd,
+    squeue::{self, Flags},
+    types::{Fd, SubmitArgs},
+    IoUring,
+    Parameters,
+    Probe,
+    SubmissionQueue,
+    Submitter,
+};
+use thunderdome::{Arena, Index};
+
+pub use self::introspection::{Histogram, OpcodeStats, OperationInfo, OperationStatus, Stats};
+use self::trace::Span;
+
+/// Strongly typed handle to a submitted operation.
+///
+/// We're using a generational arena to ensure that it's practically impossible
+/// for a reused index to be confused with some rogue operation pointing at the
+/// same slot.
+#[derive(Debug, Clone, Copy)]
+#[must_use]
+pub struct OperationId(pub(crate) Index);
+
+/// The tracked state of an operation that's stored by the reactor.
+enum OperationState {
+    /// The operation has been submitted and a caller task is waiting to be
+    /// notified of progress.
+    Waiting(Waker),
+    /// The operation has completed and the waiting task has been notified.
+    ///
+    /// This value will be passed away on the next call to
+    /// [`Reactor::poll_completion`].
+    Completed(cqueue::Entry),
+    /// The operation is producing completions at a faster rate than they're
+    /// consumed.
+    Buffering(VecDeque<cqueue::Entry>),
+    /// The operation was never submitted due to the error, which will be passed
+    /// away on the next call to [`Reactor::poll_completion`].
+    Failed(Error),
+    /// Nobody is waiting for the operation.
+    ///
+    /// Either the operation was created without passing a [`Waker`] or
+    /// [`Reactor::ignore_operation`] has been called.
+    Ignored(Option<Box<dyn Any>>),
+}
+
+/// Entry waiting to be pushed onto the submission queue.
+struct Queued {
+    entry: squeue::Entry,
+    /// Whether the entry is linked to the one queued after it, as the flags of
+    /// an entry can't be read back.
+    linked: bool,
+}
+
+/// Reason for failing a whole chain instead of pushing it.
+#[derive(Clone, Copy)]
+enum Rejection {
+    Backpressure(Backpressure),
+    Unsupported(Unsupported),
+}
+
+impl From<Rejection> for Error {
+    fn from(rejection: Rejection) -> Self {
+        match rejection {
+            Rejection::Backpressure(backpressure) => backpressure.into(),
+            Rejection::Unsupported(unsupported) => unsupported.into(),
+        }
+    }
+}
+
+/// Operation state along with details kept around for introspection.
+struct Tracked {
+    opcode: u8,
+    queued: Instant,
+    span: Span,
+    state: OperationState,
+    submitted: Option<Instant>,
+}
+
+/// Reactor for submitting and waiting for operations.
+///
+/// # Parameter safety
+///
+/// We internally queue up operations instead of pushing them to the submission
+/// queue immediately in order to make use of `IORING_FEAT_SUBMIT_STABLE`, which
+/// allows us to assume that all operations that don't have mutable buffers
+/// (which must outlast the entire operation lifecycle) can safely be dropped
+/// as long as their drop implementation ensures the registered entry doesn't
+/// ever actually get submitted to the kernel.
+///
+/// This is done as we aren't able to remove submitted entries from the real
+/// queue shared with the kernel.
+///
+/// # Reactor mutability
+///
+/// This time I'm choosing to just surface the fact that we need interior
+/// mutability one way or another and the methods just take mutable references
+/// instead of hiding this detail.
+///
+/// This is required as a reference to the reactor will necessarily be stored in
+/// multiple futures, despite knowing that only one will get polled at a time in
+/// the single threaded context that `io_uring` already necessitates.
+///
+/// Theoretically [`Context::ext`] could be used for this and get injected
+/// inside some kind of `block_on` style call, but I don't want to depend on
+/// unstable features, so [`enter`] and [`current`] provide a thread-local
+/// alternative for when threading the reactor through is inconvenient.
+///
+/// # Messages
+///
+/// Generations of arena indices are never zero, so user data values below
+/// `2^32` can't be confused with tracked operations and are instead reserved
+/// for completions posted by other rings, which are queued up separately.
+///
+/// Values from [`Reactor::MESSAGE_TAG_LIMIT`] onwards are reserved for internal
+/// use, such as [`Reactor::WAKEUP_TAG`] for waking up the reactor from other
+/// threads. Completions that don't correspond to anything known are counted
+/// instead of being treated as fatal.
+#[must_use]
+pub struct Reactor {
+    completion_eventfd: Option<OwnedFd>,
+    latencies: BTreeMap<u8, Histogram>,
+    message_waker: Option<Waker>,
+    messages: VecDeque<cqueue::Entry>,
+    probe: Option<Probe>,
+    ring: IoUring,
+    stats: Stats,
+    submission_waker: Option<Waker>,
+    tracked: Arena<Tracked>,
+    unsubmitted: IndexMap<Index, Queued, FnvBuildHasher>,
+    wakeup: Option<Arc<OwnedFd>>,
+    wakeup_armed: bool,
+}
+
+impl Reactor {
+    /// Message user data from which onwards values are reserved for internal
+    /// use instead of being available for messages.
+    pub const MESSAGE_TAG_LIMIT: u32 = 0xffff_0000;
+    /// Message user data reserved for remote wakeups.
+    pub const WAKEUP_TAG: u64 = 0xffff_ffff;
+
+    fn build(entries: u32, completion_entries: Option<u32>) -> Result<Self> {
+        let capacity = entries.try_into().unwrap_or(usize::MAX);
+        let mut builder = IoUring::builder();
+        // the taskrun flag tells non-blocking processing when to enter anyway
+        builder
+            .setup_coop_taskrun()
+            .setup_taskrun_flag()
+            .setup_single_issuer();
+
+        if let Some(completion_entries) = completion_entries {
+            builder.setup_cqsize(completion_entries);
+        }
+
+        let ring = builder.build(entries)?;
+
+        // kernels too old for probing are left to reject operations by
+        // themselves
+        let mut probe = Probe::new();
+        let probe = ring.submitter().register_probe(&mut probe).map(|()| probe);
+
+        Ok(Self {
+            ring,
+            probe: probe.ok(),
+            tracked: Arena::with_capacity(capacity),
+            unsubmitted: IndexMap::with_capacity_and_hasher(capacity, FnvBuildHasher::default()),
+            messages: VecDeque::new(),
+            message_waker: None,
+            submission_waker: None,
+            wakeup: None,
+            wakeup_armed: false,
+            completion_eventfd: None,
+            stats: Stats::default(),
+            latencies: BTreeMap::new(),
+        })
+    }
+
+    /// Number of completions the kernel has had to drop due to the completion
+    /// queue overflowing, which only happens on kernels without
+    /// `IORING_FEAT_NODROP`.
+    #[must_use]
+    pub fn dropped_completions(&mut self) -> u32 {
+        self.ring.completion().overflow()
+    }
+
+    /// List every operation that's still tracked, such as for figuring out
+    /// what ignored operations are being kept alive.
+    #[must_use]
+    pub fn dump_operations(&self) -> Vec<OperationInfo> {
+        let now = Instant::now();
+
+        self.tracked
+            .iter()
+            .map(|(index, tracked)| OperationInfo {
+                id: OperationId(index),
+                opcode: tracked.opcode,
+                status: match tracked.state {
+                    _ if self.unsubmitted.contains_key(&index) => OperationStatus::Unsubmitted,
+                    OperationState::Waiting(_) => OperationStatus::Waiting,
+                    OperationState::Completed(_) => OperationStatus::Completed,
+                    OperationState::Buffering(_) => OperationStatus::Buffering,
+                    OperationState::Ignored(_) => OperationStatus::Ignored,
+                    OperationState::Failed(_) => OperationStatus::Failed,
+                },
+                age: now.saturating_duration_since(tracked.queued),
+            })
+            .collect()
+    }
+
+    /// Fail an operation that was taken off the queue without being submitted.
+    fn fail_operation(&mut self, index: Index, error: Error) {
+        let Some(tracked) = self.tracked.get_mut(index) else {
+            return;
+        };
+
+        tracked.span.completed(
+            -error.raw_os_error().unwrap_or(libc::EINVAL),
+            Duration::ZERO,
+        );
+
+        match std::mem::replace(&mut tracked.state, OperationState::Failed(error)) {
+            OperationState::Waiting(waker) => waker.wake(),
+            // nothing will ever poll it and the parameters were never used
+            OperationState::Ignored(_) => _ = self.tracked.remove(index).unwrap(),
+            OperationState::Completed(_)
+            | OperationState::Buffering(_)
+            | OperationState::Failed(_) => unreachable!(),
+        }
+    }
+
+    /// Take queued chains off the queue for as long as they get pushed,
+    /// returning how many entries were taken off.
+    ///
+    /// Linked chains are pushed as a whole, as the kernel would otherwise
+    /// consider a partially pushed chain to end at its last pushed entry, and
+    /// ones longer than the whole submission queue or with opcodes the kernel
+    /// doesn't support fail instead. Chains pushed before pushing fails are
+    /// still taken off, so they never get pushed twice.
+    fn flush_chains<F>(&mut self, mut push: F) -> Result<usize>
+    where
+        F: FnMut(&mut IoUring, &mut Stats, &[squeue::Entry]) -> Result<bool>,
+    {
+        let capacity = self.ring.submission().capacity();
+        let mut flushed = 0;
+        let mut rejected = Vec::new();
+        let mut chain = Vec::new();
+        let mut result = Ok(());
+
+        for Queued { entry, linked } in self.unsubmitted.values() {
+            chain.push(entry.clone());
+
+            if *linked && flushed + chain.len() < self.unsubmitted.len() {
+                continue;
+            }
+
+            let unsupported = chain
+                .iter()
+                .map(|entry| u8::try_from(entry.get_opcode()).unwrap_or(u8::MAX))
+                .find(|&opcode| !self.supports(opcode));
+
+            // the kernel would fail the chain with a vague error or it would
+            // never fit, so it's failed instead of holding up everything
+            // queued after it
+            let rejection = match unsupported {
+                Some(opcode) => Some(Rejection::Unsupported(Unsupported { opcode })),
+                None if chain.len() > capacity => {
+                    Some(Rejection::Backpressure(Backpressure::ChainTooLong {
+                        length: chain.len(),
+                        capacity,
+                    }))
+                }
+                None => None,
+            };
+
+            if let Some(rejection) = rejection {
+                rejected.push((flushed..flushed + chain.len(), rejection));
+                flushed += chain.len();
+                chain.clear();
+                continue;
+            }
+
+            match push(&mut self.ring, &mut self.stats, &chain) {
+                Ok(true) => (),
+                Ok(false) => break,
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+
+            flushed += chain.len();
+            chain.clear();
+        }
+
+        let now = Instant::now();
+        let mut pushed = 0_u64;
+        let mut failed = Vec::new();
+
+        for (position, (index, Queued { entry, .. })) in
+            self.unsubmitted.drain(..flushed).enumerate()
+        {
+            if let Some((_, rejection)) =
+                rejected.iter().find(|(range, _)| range.contains(&position))
+            {
+                failed.push((index, *rejection));
+                continue;
+            }
+
+            let opcode = u8::try_from(entry.get_opcode()).unwrap_or(u8::MAX);
+            self.stats.opcode(opcode).submissions += 1;
+            pushed += 1;
+
+            if let Some(tracked) = self.tracked.get_mut(index) {
+                tracked.submitted = Some(now);
+                tracked.span.submitted();
+            }
+        }
+
+        for (index, rejection) in failed {
+            self.fail_operation(index, rejection.into());
+        }
+
+        self.stats.submissions += pushed;
+        result.map(|()| flushed)
+    }
+
+    /// Push queued entries onto the submission queue, returning how many were
+    /// taken off the queue, including ones that failed instead of being pushed.
+    fn flush_unsubmitted(&mut self) -> Result<usize> {
+        // the wakeup poll is multishot, but it might still get terminated
+        let wakeup = self
+            .wakeup
+            .as_ref()
+            .filter(|_| !self.wakeup_armed)
+            .map(|eventfd| {
+                PollAdd::new(Fd(eventfd.as_raw_fd()), readable())
+                    .multi(true)
+                    .build()
+                    .user_data(Self::WAKEUP_TAG)
+            });
+
+        let mut pushed = 0;
+
+        if let Some(entry) = wakeup {
+            let (submitter, mut submission, _) = self.ring.split();
+
+            // SAFETY: the eventfd is kept alive for as long as the reactor
+            if unsafe { push_chain(&submitter, &mut submission, &mut self.stats, &[entry]) }? {
+                self.wakeup_armed = true;
+                self.stats.submissions += 1;
+                pushed += 1;
+            }
+        }
+
+        let flushed = self.flush_chains(|ring, stats, chain| {
+            let (submitter, mut submission, _) = ring.split();
+
+            // SAFETY: initial inserter guaranteed validity
+            unsafe { push_chain(&submitter, &mut submission, stats, chain) }
+        })?;
+
+        Ok(pushed + flushed)
+    }
+
+    /// Mark a submitted operation as ignored.
+    ///
+    /// The state parameter allows callers to uphold the safety requirements
+    /// through handling the situation when the operation has already been
+    /// submitted and the parameters must be kept alive.
+    ///
+    /// Operations that are no longer tracked are counted in
+    /// [`Stats::unknown_operations`] and otherwise left alone.
+    ///
+    /// # Panics
+    ///
+    /// If an internal sanity check assertion fails.
+    pub fn ignore_operation(
+        &mut self,
+        OperationId(index): OperationId,
+        data: Option<Box<dyn Any>>,
+    ) {
+        if self.unsubmitted.shift_remove(&index).is_some() {
+            self.tracked.remove(index).unwrap().span.ignored(false);
+            return;
+        }
+
+        let Some(Tracked { state, span, .. }) = self.tracked.get_mut(index) else {
+            self.stats.unknown_operations += 1;
+            return;
+        };
+
+        span.ignored(true);
+
+        match std::mem::replace(state, OperationState::Ignored(data)) {
+            OperationState::Waiting(_) | OperationState::Ignored(_) => (),
+            OperationState::Completed(entry) if cqueue::more(entry.flags()) => (),
+            OperationState::Completed(_) | OperationState::Failed(_) => {
+                _ = self.tracked.remove(index).unwrap();
+            }
+            OperationState::Buffering(entries) => match entries.into_iter().last() {
+                Some(entry) if cqueue::more(entry.flags()) => (),
+                Some(_) => _ = self.tracked.remove(index).unwrap(),
+                None => (),
+            },
+        }
+    }
+
+    fn install_eventfd(&mut self, asynchronous: bool) -> Result<BorrowedFd<'_>> {
+        self.unregister_eventfd()?;
+
+        let eventfd = eventfd()?;
+        let submitter = self.ring.submitter();
+
+        if asynchronous {
+            submitter.register_eventfd_async(eventfd.as_raw_fd())?;
+        } else {
+            submitter.register_eventfd(eventfd.as_raw_fd())?;
+        }
+
+        let eventfd: &OwnedFd = self.completion_eventfd.insert(eventfd);
+        Ok(eventfd.as_fd())
+    }
+
+    /// Get histograms of the time between operations getting pushed onto the
+    /// submission queue and their completions being processed, by opcode.
+    #[must_use]
+    pub const fn latencies(&self) -> &BTreeMap<u8, Histogram> {
+        &self.latencies
+    }
+
+    /// Initialize the reactor with the specified queue size.
+    ///
+    /// This queue size is also used to preallocate storage for internal state.
+    ///
+    /// # Errors
+    ///
+    /// If initializing the internal `io_uring` instance fails.
+    pub fn new(entries: u32) -> Result<Self> {
+        Self::build(entries, None)
+    }
+
+    /// Get the parameters the ring was set up with, which includes the
+    /// features supported by the kernel.
+    #[must_use]
+    pub fn parameters(&self) -> &Parameters {
+        self.ring.params()
+    }
+
+    /// Poll for an operation's completion.
+    ///
+    /// # Errors
+    ///
+    /// If the operation failed without ever being submitted, or with
+    /// [`ErrorKind::NotFound`] if it isn't tracked, such as when its final
+    /// completion was already polled or it belongs to another reactor. The
+    /// latter is also counted in [`Stats::unknown_operations`].
+    ///
+    /// # Panics
+    ///
+    /// If an internal sanity check assertion fails.
+    pub fn poll_completion(
+        &mut self,
+        OperationId(index): OperationId,
+        context: &Context,
+    ) -> Poll<Result<cqueue::Entry>> {
+        let Some(Tracked { state, span, .. }) = self.tracked.get_mut(index) else {
+            self.stats.unknown_operations += 1;
+
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::NotFound,
+                "operation isn't tracked by the reactor",
+            )));
+        };
+
+        match state {
+            OperationState::Waiting(waker) => {
+                if !waker.will_wake(context.waker()) {
+                    context.waker().clone_into(waker);
+                }
+
+                Poll::Pending
+            }
+            OperationState::Completed(entry) => {
+                span.polled(entry.result());
+
+                let output = if cqueue::more(entry.flags()) {
+                    std::mem::replace(state, OperationState::Waiting(context.waker().clone()))
+                } else {
+                    self.tracked.remove(index).unwrap().state
+                };
+
+                let OperationState::Completed(entry) = output else {
+                    unreachable!();
+                };
+
+                Poll::Ready(Ok(entry))
+            }
+            OperationState::Buffering(entries) => {
+                let Some(entry) = entries.pop_front() else {
+                    return Poll::Pending;
+                };
+
+                span.polled(entry.result());
+
+                if !entries.is_empty() {
+                    context.waker().wake_by_ref();
+                    return Poll::Ready(Ok(entry));
+                }
+
+                if !cqueue::more(entry.flags()) {
+                    self.tracked.remove(index).unwrap();
+                    return Poll::Ready(Ok(entry));
+                }
+
+                *state = OperationState::Waiting(context.waker().clone());
+                Poll::Ready(Ok(entry))
+            }
+            OperationState::Failed(_) => {
+                let OperationState::Failed(error) = self.tracked.remove(index).unwrap().state
+                else {
+                    unreachable!();
+                };
+
+                Poll::Ready(Err(error))
+            }
+            OperationState::Ignored(data) => {
+                assert!(
+                    data.is_none(),
+                    "an explicitly forgotten operation shouldn't be polled again"
+                );
+
+                *state = OperationState::Waiting(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Poll for a message posted by another ring.
+    ///
+    /// Only the most recent caller gets notified of new messages.
+    pub fn poll_message(&mut self, context: &Context) -> Poll<cqueue::Entry> {
+        if let Some(entry) = self.messages.pop_front() {
+            return Poll::Ready(entry);
+        }
+
+        match &mut self.message_waker {
+            Some(waker) => waker.clone_from(context.waker()),
+            waker @ None => *waker = Some(context.waker().clone()),
+        }
+
+        Poll::Pending
+    }
+
+    /// Poll for entries queued up for the next submission, registering the
+    /// waker to be woken once the next one gets queued otherwise.
+    ///
+    /// This is meant for other event loops driving the reactor, which might
+    /// otherwise sleep without anything submitting the entries.
+    pub fn poll_unsubmitted(&mut self, context: &Context) -> Poll<()> {
+        if !self.unsubmitted.is_empty() {
+            return Poll::Ready(());
+        }
+
+        match &mut self.submission_waker {
+            Some(waker) => waker.clone_from(context.waker()),
+            waker @ None => *waker = Some(context.waker().clone()),
+        }
+
+        Poll::Pending
+    }
+
+    /// Submit queued entries and handle whatever completions are available,
+    /// without ever waiting for more.
+    ///
+    /// This also resets the `eventfd` registered for completions, so it's
+    /// meant to be called whenever that becomes readable. Returns the number
+    /// of completions that were handled.
+    ///
+    /// # Errors
+    ///
+    /// If entering the kernel fails, with [`Backpressure`] as the inner error
+    /// when the failure is due to entries not being accepted.
+    pub fn process_completions(&mut self) -> Result<usize> {
+        // reset first, so completions posted from here on signal it again
+        if let Some(eventfd) = &self.completion_eventfd {
+            drain_wakeups(eventfd);
+        }
+
+        self.flush_unsubmitted()?;
+
+        let (submitter, mut submission, mut completion) = self.ring.split();
+        submission.sync();
+
+        // the kernel also has to be entered for it to run deferred work
+        if !submission.is_empty() || submission.taskrun() || submission.cq_overflow() {
+            self.stats.enters += 1;
+
+            match submitter.submit() {
+                Ok(_) => (),
+                Err(error) if is_busy(&error) => {
+                    completion.sync();
+
+                    if completion.is_empty() {
+                        return Err(Backpressure::Busy.into());
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        drop((submission, completion));
+        self.reap_completions()
+    }
+
+    /// Track a new operation and queue its entry.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`Reactor::queue_submission`] apply.
+    unsafe fn queue(
+        &mut self,
+        entry: squeue::Entry,
+        linked: bool,
+        context: Option<&Context>,
+    ) -> OperationId {
+        let initial = context
+            .map(Context::waker)
+            .cloned()
+            .map_or(OperationState::Ignored(None), OperationState::Waiting);
+
+        let opcode = u8::try_from(entry.get_opcode()).unwrap_or(u8::MAX);
+        let index = self.tracked.insert(Tracked {
+            state: initial,
+            opcode,
+            queued: Instant::now(),
+            submitted: None,
+            // the index is only known after inserting
+            span: Span::none(),
+        });
+
+        self.tracked[index].span = Span::queued(index.to_bits(), opcode);
+
+        let entry = entry.user_data(index.to_bits());
+        self.unsubmitted.insert(index, Queued { entry, linked });
+
+        if let Some(waker) = self.submission_waker.take() {
+            waker.wake();
+        }
+
+        OperationId(index)
+    }
+
+    /// Queue an operation that the one queued right after it is linked to,
+    /// with the link being either [`Flags::IO_LINK`] or [`Flags::IO_HARDLINK`].
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`Reactor::queue_submission`] apply.
+    pub unsafe fn queue_linked_submission(
+        &mut self,
+        entry: squeue::Entry,
+        link: Flags,
+        context: Option<&Context>,
+    ) -> OperationId {
+        let linked = link.intersects(Flags::IO_LINK | Flags::IO_HARDLINK);
+
+        // SAFETY: guaranteed by the caller
+        unsafe { self.queue(entry.flags(link), linked, context) }
+    }
+
+    /// Queue an operation to get submitted and return an unique identifier to
+    /// it's internal state.
+    ///
+    /// This function only optionally takes in a [`Context`] in order to allow
+    /// that an operation isn't initially waited by anything, as the poll
+    /// implementation will also update the waker regardless.
+    ///
+    /// Operations with opcodes the kernel doesn't support fail with
+    /// [`Unsupported`] once they would get flushed, along with the rest of
+    /// their linked chain, rather than being left for the kernel to fail with
+    /// `EINVAL`.
+    ///
+    /// Entries linked to the next one have to go through
+    /// [`Reactor::queue_linked_submission`] instead of having the link flags
+    /// set beforehand, as chains are kept together when flushing.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that any parameters are valid and will be kept
+    /// valid according to the kernel's requirements.
+    pub unsafe fn queue_submission(
+        &mut self,
+        entry: squeue::Entry,
+        context: Option<&Context>,
+    ) -> OperationId {
+        // SAFETY: guaranteed by the caller
+        unsafe { self.queue(entry, false, context) }
+    }
+
+    /// Handle available completions, flushing ones that overflowed the
+    /// completion queue along the way.
+    fn reap_completions(&mut self) -> Result<usize> {
+        let (submitter, submission, mut completion) = self.ring.split();
+        completion.sync();
+
+        // completions only get noticed here, so this is when they're timed
+        let mut now = Instant::now();
+        let mut reaped = 0;
+
+        loop {
+            for entry in completion.by_ref() {
+                self.stats.completions += 1;
+                reaped += 1;
+
+                let Some(index) = Index::from_bits(entry.user_data()) else {
+                    match entry.user_data() {
+                        Self::WAKEUP_TAG => {
+                            if let Some(eventfd) = &self.wakeup {
+                                drain_wakeups(eventfd);
+                            }
+
+                            self.wakeup_armed &= cqueue::more(entry.flags());
+                        }
+                        tag if tag < Self::MESSAGE_TAG_LIMIT.into() => {
+                            self.messages.push_back(entry);
+
+                            if let Some(waker) = self.message_waker.take() {
+                                waker.wake();
+                            }
+                        }
+                        _ => self.stats.unknown_completions += 1,
+                    }
+
+                    continue;
+                };
+
+                // stray completions of operations that are no longer tracked
+                let Some(tracked) = self.tracked.get_mut(index) else {
+                    self.stats.unknown_completions += 1;
+                    continue;
+                };
+
+                let counters = self.stats.opcode(tracked.opcode);
+                counters.completions += 1;
+                counters.errors += u64::from(entry.result().is_negative());
+
+                let latency = now.saturating_duration_since(tracked.submitted.unwrap_or(now));
+                tracked.span.completed(entry.result(), latency);
+
+                self.latencies
+                    .entry(tracked.opcode)
+                    .or_default()
+                    .record(latency);
+
+                let state = &mut tracked.state;
+
+                match state {
+                    state @ OperationState::Waiting(_) => {
+                        let previous = std::mem::replace(state, OperationState::Completed(entry));
+
+                        let OperationState::Waiting(waker) = previous else {
+                            unreachable!();
+                        };
+
+                        waker.wake();
+                    }
+                    state @ OperationState::Completed(_) => {
+                        let replacement = OperationState::Buffering(VecDeque::with_capacity(2));
+                        let previous = std::mem::replace(state, replacement);
+
+                        let OperationState::Completed(previous) = previous else {
+                            unreachable!();
+                        };
+
+                        let OperationState::Buffering(entries) = state else {
+                            unreachable!();
+                        };
+
+                        entries.push_back(previous);
+                        entries.push_back(entry);
+                    }
+                    OperationState::Buffering(entries) => entries.push_back(entry),
+                    OperationState::Ignored(_) if cqueue::more(entry.flags()) => (),
+                    OperationState::Ignored(_) => _ = self.tracked.remove(index).unwrap(),
+                    // never submitted, so this can't actually be its completion
+                    OperationState::Failed(_) => self.stats.unknown_completions += 1,
+                }
+            }
+
+            if !submission.cq_overflow() {
+                break;
+            }
+
+            // completions that didn't fit are buffered by the kernel until it's
+            // entered again, which is only worth doing after making space, and
+            // the kernel only sees that space once the head is synced
+            completion.sync();
+            self.stats.overflows += 1;
+            self.stats.enters += 1;
+
+            match submitter.submit() {
+                Ok(_) => {
+                    completion.sync();
+                    now = Instant::now();
+                }
+                Err(error) if is_busy(&error) => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(reaped)
+    }
+
+    /// Register an `eventfd` that the kernel signals whenever it posts
+    /// completions, replacing any previously registered one.
+    ///
+    /// This allows other event loops to watch a single descriptor and drive
+    /// the reactor with [`Reactor::process_completions`] once it becomes
+    /// readable, rather than blocking in [`Reactor::wait_for_progress`].
+    ///
+    /// # Errors
+    ///
+    /// If creating or registering the `eventfd` fails.
+    pub fn register_eventfd(&mut self) -> Result<BorrowedFd<'_>> {
+        self.install_eventfd(false)
+    }
+
+    /// Register an `eventfd` like [`Reactor::register_eventfd`], except that
+    /// it only gets signaled for operations that didn't complete inline while
+    /// being submitted.
+    ///
+    /// # Errors
+    ///
+    /// If creating or registering the `eventfd` fails.
+    pub fn register_eventfd_async(&mut self) -> Result<BorrowedFd<'_>> {
+        self.install_eventfd(true)
+    }
+
+    /// Register a sparse table of files for the kernel to install files into,
+    /// such as the ones sent by other rings.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn register_sparse_files(&mut self, count: u32) -> Result<()> {
+        self.ring.submitter().register_files_sparse(count)
+    }
+
+    /// Get a handle for waking up the reactor from other threads.
+    ///
+    /// This lazily sets up an `eventfd` that's kept polled for as long as the
+    /// reactor lives.
+    ///
+    /// # Errors
+    ///
+    /// If creating the `eventfd` fails.
+    pub fn remote_handle(&mut self) -> Result<RemoteHandle> {
+        if let Some(eventfd) = &self.wakeup {
+            return Ok(RemoteHandle {
+                eventfd: Arc::clone(eventfd),
+            });
+        }
+
+        let eventfd = Arc::new(eventfd()?);
+        self.wakeup = Some(Arc::clone(&eventfd));
+        self.wakeup_armed = false;
+
+        Ok(RemoteHandle { eventfd })
+    }
+
+    /// Fail with [`Unsupported`] as the inner error unless the kernel supports
+    /// the specified opcode.
+    ///
+    /// # Errors
+    ///
+    /// If the opcode isn't supported.
+    pub fn require(&self, opcode: u8) -> Result<()> {
+        if self.supports(opcode) {
+            return Ok(());
+        }
+
+        Err(Unsupported { opcode }.into())
+    }
+
+    /// Clear the recorded latencies, such as after exporting them.
+    pub fn reset_latencies(&mut self) {
+        self.latencies.clear();
+    }
+
+    /// Get the number of tracked operations by their state along with
+    /// counters accumulated over the lifetime of the reactor.
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        let mut stats = self.stats.clone();
+        stats.unsubmitted = self.unsubmitted.len();
+
+        for (_, tracked) in &self.tracked {
+            match tracked.state {
+                OperationState::Waiting(_) => stats.waiting += 1,
+                OperationState::Completed(_) => stats.completed += 1,
+                OperationState::Buffering(_) => stats.buffering += 1,
+                OperationState::Ignored(_) => stats.ignored += 1,
+                OperationState::Failed(_) => stats.failed += 1,
+            }
+        }
+
+        stats
+    }
+
+    /// Check whether the kernel supports the specified opcode, such as
+    /// [`io_uring::opcode::FutexWait::CODE`].
+    ///
+    /// Every opcode is assumed to be supported on kernels that are too old
+    /// for probing.
+    #[must_use]
+    pub fn supports(&self, opcode: u8) -> bool {
+        self.probe
+            .as_ref()
+            .is_none_or(|probe| probe.is_supported(opcode))
+    }
+
+    /// Unregister and close the `eventfd` registered for completions, if any.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn unregister_eventfd(&mut self) -> Result<()> {
+        if self.completion_eventfd.take().is_some() {
+            self.ring.submitter().unregister_eventfd()?;
+        }
+
+        Ok(())
+    }
+
+    /// Clear a slot in the registered file table.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying system call fails.
+    pub fn unregister_file(&mut self, slot: u32) -> Result<()> {
+        self.ring
+            .submitter()
+            .register_files_update(slot, &[-1])
+            .map(drop)
+    }
+
+    /// Submit queued entries and wait until tracked operations progress or the
+    /// provided timeout elapses.
+    ///
+    /// Entries that don't fit onto the submission queue are kept queued for
+    /// the next call, with linked chains never getting split between calls.
+    /// Operations in chains that are longer than the whole submission queue
+    /// fail with [`Backpressure::ChainTooLong`] instead, and ones in chains
+    /// with opcodes the kernel doesn't support fail with [`Unsupported`].
+    ///
+    /// # Errors
+    ///
+    /// If entering the kernel fails, with [`Backpressure`] as the inner error
+    /// when the failure is due to entries not being accepted.
+    ///
+    /// # Panics
+    ///
+    /// If an internal sanity check assertion fails.
+    pub fn wait_for_progress(&mut self, timeout: Option<Duration>) -> Result<()> {
+        let flushed = self.flush_unsubmitted()?;
+        let (submitter, _, mut completion) = self.ring.split();
+
+        // uncertain what the ideal logic should be, but we definitely want to block if
+        // we haven't managed to submit operations and nothing has completed
+        let wanted = usize::from(flushed == 0 && completion.is_empty());
+        self.stats.enters += 1;
+
+        let result = timeout.map_or_else(
+            || submitter.submit_and_wait(wanted),
+            |timeout| {
+                submitter.submit_with_args(wanted, &SubmitArgs::new().timespec(&timeout.into()))
+            },
+        );
+
+        completion.sync();
+
+        match result {
+            Ok(_) => (),
+            Err(error) if error.raw_os_error() == Some(libc::ETIME) => assert!(timeout.is_some()),
+            // reaping completions is what allows the kernel to accept more
+            Err(error) if is_busy(&error) && !completion.is_empty() => (),
+            Err(error) if is_busy(&error) => return Err(Backpressure::Busy.into()),
+            Err(error) => return Err(error),
+        }
+
+        drop(completion);
+        self.reap_completions()?;
+
+        Ok(())
+    }
+
+    /// Initialize the reactor with a completion queue that's the specified
+    /// factor larger than the submission queue.
+    ///
+    /// The kernel defaults to twice the size, which might not be enough for
+    /// multishot operations producing completions in bursts.
+    ///
+    /// # Errors
+    ///
+    /// If initializing the internal `io_uring` instance fails, such as when
+    /// the resulting size is too large, or with [`ErrorKind::InvalidInput`] if
+    /// the factor doesn't make the completion queue larger.
+    pub fn with_completion_factor(entries: u32, factor: u32) -> Result<Self> {
+        if factor <= 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "completion queue factor must be larger than one",
+            ));
+        }
+
+        let completion_entries = entries
+            .checked_mul(factor)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidInput))?;
+
+        Self::build(entries, Some(completion_entries))
+    }
+}
+
+impl AsFd for Reactor {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the ring stays open for as long as it's borrowed
+        unsafe { BorrowedFd::borrow_raw(self.ring.as_raw_fd()) }
+    }
+}
+
+/// Reason for queued entries not making it onto the submission queue, which is
+/// surfaced as the inner error of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Backpressure {
+    /// The kernel refused to consume entries until completions get reaped.
+    Busy,
+    /// A linked chain is longer than the submission queue, so it can never be
+    /// submitted as a whole and every operation in it fails with this instead.
+    ChainTooLong { length: usize, capacity: usize },
+}
+
+impl Backpressure {
+    /// Get the backpressure behind an error, if that's what caused it.
+    #[must_use]
+    pub fn from_error(error: &Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl Display for Backpressure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Busy => f.write_str("completion queue is backed up"),
+            Self::ChainTooLong { length, capacity } => write!(
+                f,
+                "linked chain of {length} entries exceeds the submission queue size of {capacity}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Backpressure {}
+
+impl From<Backpressure> for Error {
+    fn from(backpressure: Backpressure) -> Self {
+        Self::new(ErrorKind::ResourceBusy, backpressure)
+    }
+}
+
+/// Error for an opcode the kernel doesn't support, which is surfaced as the
+/// inner error of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsupported {
+    opcode: u8,
+}
+
+impl Unsupported {
+    /// Get the unsupported opcode behind an error, if that's what caused it.
+    #[must_use]
+    pub fn from_error(error: &Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+
+    /// The opcode that isn't supported.
+    #[must_use]
+    pub const fn opcode(&self) -> u8 {
+        self.opcode
+    }
+}
+
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "opcode {} isn't supported by the kernel", self.opcode)
+    }
+}
+
+impl std::error::Error for Unsupported {}
+
+impl From<Unsupported> for Error {
+    fn from(unsupported: Unsupported) -> Self {
+        Self::new(ErrorKind::Unsupported, unsupported)
+    }
+}
+
+/// Handle for waking up a reactor blocked waiting for progress, which can be
+/// sent to and used from any thread.
+#[derive(Clone)]
+#[must_use]
+pub struct RemoteHandle {
+    eventfd: Arc<OwnedFd>,
+}
+
+impl RemoteHandle {
+    /// Wake up the reactor, making any ongoing or the next call to
+    /// [`Reactor::wait_for_progress`] return.
+    ///
+    /// # Errors
+    ///
+    /// If writing to the underlying `eventfd` fails.
+    pub fn wake(&self) -> Result<()> {
+        let value = 1_u64.to_ne_bytes();
+
+        // SAFETY: the buffer is valid for the duration of the call
+        let written =
+            unsafe { libc::write(self.eventfd.as_raw_fd(), value.as_ptr().cast(), value.len()) };
+
+        match written {
+            0.. => Ok(()),
+            // the counter is saturated, so a wakeup is already pending
+            _ if Error::last_os_error().kind() == ErrorKind::WouldBlock => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<RefCell<Reactor>>>> = const { RefCell::new(None) };
+}
+
+/// Guard for restoring the previously current reactor when dropped.
+#[must_use]
+pub struct EnterGuard {
+    previous: Option<Rc<RefCell<Reactor>>>,
+}
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.replace(self.previous.take()));
+    }
+}
+
+/// Push a linked chain onto the submission queue as a whole, submitting the
+/// already pushed entries first if there isn't enough space for it.
+///
+/// Returns whether the chain was pushed, as the kernel might not consume any
+/// entries while completions are backed up.
+///
+/// # Safety
+///
+/// The same requirements as for [`SubmissionQueue::push_multiple`] apply.
+unsafe fn push_chain(
+    submitter: &Submitter,
+    submission: &mut SubmissionQueue,
+    stats: &mut Stats,
+    chain: &[squeue::Entry],
+) -> Result<bool> {
+    // SAFETY: guaranteed by the caller
+    if unsafe { submission.push_multiple(chain) }.is_ok() {
+        return Ok(true);
+    }
+
+    submission.sync();
+    stats.enters += 1;
+
+    match submitter.submit() {
+        Ok(_) => submission.sync(),
+        Err(error) if is_busy(&error) => return Ok(false),
+        Err(error) => return Err(error),
+    }
+
+    // SAFETY: guaranteed by the caller
+    Ok(unsafe { submission.push_multiple(chain) }.is_ok())
+}
+
+/// Check whether the kernel refused entries due to completions being backed
+/// up.
+fn is_busy(error: &Error) -> bool {
+    matches!(error.raw_os_error(), Some(libc::EBUSY | libc::EAGAIN))
+}
+
+/// Event mask for polling for readability.
+fn readable() -> u32 {
+    u32::from(libc::POLLIN.cast_unsigned())
+}
+
+/// Create a non-blocking `eventfd`.
+fn eventfd() -> Result<OwnedFd> {
+    // SAFETY: plain system call without any pointers
+    let descriptor = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+    if descriptor < 0 {
+        return Err(Error::last_os_error());
+    }
+
+    // SAFETY: we just created the descriptor and nothing else owns it
+    Ok(unsafe { OwnedFd::from_raw_fd(descriptor) })
+}
+
+/// Reset the counter of an `eventfd` after wakeups.
+fn drain_wakeups(eventfd: &OwnedFd) {
+    let mut buffer = [0_u8; 8];
+
+    // SAFETY: the buffer is valid for the duration of the call, and failing
+    // just means that there was nothing to drain
+    _ = unsafe {
+        libc::read(
+            eventfd.as_raw_fd(),
+            buffer.as_mut_ptr().cast(),
+            buffer.len(),
+        )
+    };
+}
+
+/// Install the reactor as the current one for this thread until the returned
+/// guard is dropped.
+pub fn enter(reactor: Rc<RefCell<Reactor>>) -> EnterGuard {
+    let previous = CURRENT.with(|current| current.replace(Some(reactor)));
+    EnterGuard { previous }
+}
+
+/// Get the reactor that's current for this thread, if there is one.
+#[must_use]
+pub fn try_current() -> Option<Rc<RefCell<Reactor>>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Get the reactor that's current for this thread.
+///
+/// # Panics
+///
+/// If no reactor has been installed through [`enter`], which the executor
+/// does while running.
+#[must_use]
+pub fn current() -> Rc<RefCell<Reactor>> {
+    try_current().expect("no reactor is current for this thread")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Error, Result},
+        task::{Context, Poll, Waker},
+    };
+
+    use io_uring::{opcode::Nop, squeue::Flags, Probe};
+
+    use super::{push_chain, Backpressure, OperationId, Reactor, Unsupported};
+
+    /// Queue a chain of no-ops that are waited for by nothing in particular.
+    fn queue_chain(reactor: &mut Reactor, length: usize) -> Vec<OperationId> {
+        let context = Context::from_waker(Waker::noop());
+
+        (0..length)
+            .map(|position| {
+                let link = if position + 1 < length {
+                    Flags::IO_LINK
+                } else {
+                    Flags::empty()
+                };
+
+                // SAFETY: no-ops don't reference anything
+                unsafe { reactor.queue_linked_submission(Nop::new().build(), link, Some(&context)) }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn failed_push_takes_pushed_chains_off_the_queue() -> Result<()> {
+        let mut reactor = Reactor::new(4)?;
+        let oversized = queue_chain(&mut reactor, 5);
+        queue_chain(&mut reactor, 2);
+        queue_chain(&mut reactor, 3);
+
+        let mut pushes = 0;
+        let result = reactor.flush_chains(|ring, stats, chain| {
+            pushes += 1;
+
+            if pushes > 1 {
+                return Err(Error::other("injected push failure"));
+            }
+
+            let (submitter, mut submission, _) = ring.split();
+
+            // SAFETY: no-ops don't reference anything
+            unsafe { push_chain(&submitter, &mut submission, stats, chain) }
+        });
+
+        assert_eq!(
+            result.map_err(|error| error.to_string()),
+            Err("injected push failure".to_owned()),
+        );
+        assert_eq!(reactor.unsubmitted.len(), 3);
+        assert_eq!(reactor.stats.submissions, 2);
+
+        // the oversized chain is failed even though the flush failed
+        let context = Context::from_waker(Waker::noop());
+
+        for id in oversized {
+            let Poll::Ready(Err(error)) = reactor.poll_completion(id, &context) else {
+                panic!("operation in the oversized chain didn't fail");
+            };
+
+            assert_eq!(
+                Backpressure::from_error(&error),
+                Some(&Backpressure::ChainTooLong {
+                    length: 5,
+                    capacity: 4,
+                }),
+            );
+        }
+
+        // only the chain that wasn't pushed yet is left for the next flush
+        assert_eq!(reactor.flush_unsubmitted()?, 3);
+        assert!(reactor.unsubmitted.is_empty());
+        assert_eq!(reactor.stats.submissions, 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_opcodes_fail_their_chain() -> Result<()> {
+        let mut reactor = Reactor::new(4)?;
+        // nothing is supported according to a probe that was never registered
+        reactor.probe = Some(Probe::new());
+        let chain = queue_chain(&mut reactor, 2);
+
+        reactor.flush_unsubmitted()?;
+        assert!(reactor.unsubmitted.is_empty());
+
+        let context = Context::from_waker(Waker::noop());
+
+        for id in chain {
+            let Poll::Ready(Err(error)) = reactor.poll_completion(id, &context) else {
+                panic!("operation with an unsupported opcode didn't fail");
+            };
+
+            assert_eq!(
+                Unsupported::from_error(&error),
+                Some(&Unsupported { opcode: Nop::CODE }),
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+//! Optional `tracing` instrumentation of the operation lifecycle, which
+//! compiles down to nothing unless the feature is enabled.
+use std::time::Duration;
+
+/// Span covering an operation from being queued until the reactor stops
+/// tracking it.
+#[cfg(feature = "tracing")]
+pub(super) struct Span(tracing::Span);
+
+#[cfg(feature = "tracing")]
+impl Span {
+    pub(super) fn completed(&self, result: i32, latency: Duration) {
+        tracing::debug!(parent: &self.0, result, ?latency, "completed");
+    }
+
+    pub(super) fn ignored(&self, submitted: bool) {
+        if submitted {
+            tracing::debug!(parent: &self.0, "ignored");
+        } else {
+            tracing::debug!(parent: &self.0, "cancelled before submission");
+        }
+    }
+
+    pub(super) const fn none() -> Self {
+        Self(tracing::Span::none())
+    }
+
+    pub(super) fn polled(&self, result: i32) {
+        tracing::trace!(parent: &self.0, result, "polled to completion");
+    }
+
+    pub(super) fn queued(id: u64, opcode: u8) -> Self {
+        let span = tracing::debug_span!("operation", id, opcode);
+        tracing::trace!(parent: &span, "queued");
+
+        Self(span)
+    }
+
+    pub(super) fn submitted(&self) {
+        tracing::trace!(parent: &self.0, "submitted");
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(super) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+#[expect(clippy::unused_self)]
+impl Span {
+    pub(super) const fn completed(&self, _: i32, _: Duration) {}
+
+    pub(super) const fn ignored(&self, _: bool) {}
+
+    pub(super) const fn none() -> Self {
+        Self
+    }
+
+    pub(super) const fn polled(&self, _: i32) {}
+
+    pub(super) const fn queued(_: u64, _: u8) -> Self {
+        Self
+    }
+
+    pub(super) const fn submitted(&self) {}
+}
@@ -0,0 +1,440 @@
+//! Multithreaded runtime running a reactor and an executor on every worker
+//! thread, with rings messaging each other to hand off work.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    future::{poll_fn, Future},
+    io::{Error, ErrorKind, Result},
+    os::fd::{AsFd as _, AsRawFd as _, BorrowedFd, OwnedFd},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        mpsc,
+        Arc,
+        Mutex,
+        PoisonError,
+    },
+    thread,
+};
+
+use fnv::FnvBuildHasher;
+use io_uring::{cqueue, opcode, IoUring};
+
+use crate::{
+    executor::{self, Executor},
+    operation::{Batch as _, FixedFdInstall, MsgRingData, MsgRingSendFd, Oneshot},
+    reactor::{self, Reactor},
+};
+
+/// Work sent over to a worker thread.
+enum Job {
+    Descriptor(Box<dyn FnOnce(OwnedFd) + Send>),
+    Spawn(Box<dyn FnOnce() + Send>),
+    Stop,
+}
+
+/// Jobs waiting for the message that notifies the worker about them.
+type Inbox = Arc<Mutex<HashMap<u32, Job, FnvBuildHasher>>>;
+
+/// Ring used for posting messages onto the worker rings from any thread.
+struct Messenger {
+    ring: IoUring,
+    sequence: u64,
+}
+
+impl Messenger {
+    fn new() -> Result<Self> {
+        let ring = IoUring::new(8)?;
+
+        // a single slot is enough as sending is serialized anyways
+        ring.submitter().register_files_sparse(1)?;
+        Ok(Self { ring, sequence: 0 })
+    }
+
+    /// Post a message that installs the descriptor into the target ring.
+    fn send_descriptor(&mut self, ring: BorrowedFd, id: u32, descriptor: &OwnedFd) -> Result<()> {
+        let submitter = self.ring.submitter();
+        submitter.register_files_update(0, &[descriptor.as_raw_fd()])?;
+
+        let result = self.submit(MsgRingSendFd::new(ring, 0, id.into()));
+
+        // the target ring holds its own reference to the file
+        self.ring.submitter().register_files_update(0, &[-1])?;
+        result
+    }
+
+    /// Post a message without anything attached.
+    fn send_message(&mut self, ring: BorrowedFd, id: u32) -> Result<()> {
+        self.submit(MsgRingData::new(ring, 0, id.into()))
+    }
+
+    /// Submit an operation and block until it completes.
+    ///
+    /// Completions are matched to the submission through the user data, as
+    /// an earlier submission that failed to be waited for can still complete
+    /// after it.
+    fn submit<O, T>(&mut self, mut operation: O) -> Result<T>
+    where
+        O: Oneshot<Output = Result<T>>,
+    {
+        self.sequence = self.sequence.wrapping_add(1);
+        let user_data = self.sequence;
+        let entry = operation.build_submission().user_data(user_data);
+
+        // SAFETY: the operation doesn't reference anything that it has to keep
+        // alive, so it doesn't matter if it completes after returning
+        unsafe { self.ring.submission().push(&entry) }
+            .map_err(|_| Error::other("messenger submission queue is full"))?;
+
+        loop {
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => (),
+                // the kernel only accepts the submission once there's space
+                // for completions, which draining below makes
+                Err(error) if is_transient(&error) => (),
+                Err(error) => {
+                    // nothing is left behind for the next submission to read
+                    self.ring.completion().for_each(drop);
+                    return Err(error);
+                }
+            }
+
+            // completions of earlier submissions are stale and get skipped
+            let completion = self
+                .ring
+                .completion()
+                .find(|entry| entry.user_data() == user_data);
+
+            if let Some(entry) = completion {
+                // SAFETY: the entry is the completion of the submission made above
+                return unsafe { operation.handle_completion(entry) };
+            }
+        }
+    }
+}
+
+/// Worker thread as seen by other threads.
+struct Worker {
+    failures: Arc<AtomicU64>,
+    inbox: Inbox,
+    ring: OwnedFd,
+}
+
+/// State shared between every handle to the runtime.
+struct Shared {
+    messenger: Mutex<Messenger>,
+    sequence: AtomicU32,
+    workers: Vec<Worker>,
+}
+
+/// Cloneable handle for sending work onto the workers of a [`Runtime`] from
+/// any thread.
+#[derive(Clone)]
+#[must_use]
+pub struct Handle {
+    shared: Arc<Shared>,
+}
+
+impl Handle {
+    /// Number of worker threads.
+    #[must_use]
+    pub fn cores(&self) -> usize {
+        self.shared.workers.len()
+    }
+
+    /// Number of descriptors sent with [`Handle::send_fd`] that reached their
+    /// worker but failed to be received, such as when it has run out of
+    /// descriptors, in which case their jobs get dropped.
+    #[must_use]
+    pub fn failed_descriptors(&self) -> u64 {
+        self.shared
+            .workers
+            .iter()
+            .map(|worker| worker.failures.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    fn send(&self, core: usize, job: Job, descriptor: Option<OwnedFd>) -> Result<()> {
+        let worker = &self.shared.workers[core];
+        // the modulo keeps clear of the tags reserved for internal use
+        let id = self.shared.sequence.fetch_add(1, Ordering::Relaxed) % Reactor::MESSAGE_TAG_LIMIT;
+
+        let mut inbox = worker.inbox.lock().unwrap_or_else(PoisonError::into_inner);
+        inbox.insert(id, job);
+        drop(inbox);
+
+        let result = {
+            let mut messenger = self
+                .shared
+                .messenger
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            match descriptor {
+                Some(descriptor) => messenger.send_descriptor(worker.ring.as_fd(), id, &descriptor),
+                None => messenger.send_message(worker.ring.as_fd(), id),
+            }
+        };
+
+        if result.is_err() {
+            let mut inbox = worker.inbox.lock().unwrap_or_else(PoisonError::into_inner);
+            inbox.remove(&id);
+        }
+
+        result
+    }
+
+    /// Send a file descriptor over to the specified worker through its ring,
+    /// spawning a task with it once it arrives.
+    ///
+    /// # Errors
+    ///
+    /// If posting the message onto the worker's ring fails, such as when the
+    /// worker has too many descriptors in flight. Failures on the worker's end
+    /// are only counted in [`Handle::failed_descriptors`].
+    ///
+    /// # Panics
+    ///
+    /// If there's no such worker.
+    pub fn send_fd<F, T>(&self, core: usize, descriptor: OwnedFd, function: F) -> Result<()>
+    where
+        F: FnOnce(OwnedFd) -> T + Send + 'static,
+        T: Future + 'static,
+        T::Output: 'static,
+    {
+        let job = Job::Descriptor(Box::new(move |descriptor| {
+            _ = executor::spawn_local(function(descriptor));
+        }));
+
+        self.send(core, job, Some(descriptor))
+    }
+
+    /// Spawn a task on the specified worker, with the future created on the
+    /// worker itself as it doesn't need to be sendable.
+    ///
+    /// # Errors
+    ///
+    /// If posting the message onto the worker's ring fails.
+    ///
+    /// # Panics
+    ///
+    /// If there's no such worker.
+    pub fn spawn_on<F, T>(&self, core: usize, function: F) -> Result<()>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Future + 'static,
+        T::Output: 'static,
+    {
+        let job = Job::Spawn(Box::new(move || {
+            // detached as the output has nowhere to go
+            _ = executor::spawn_local(function());
+        }));
+
+        self.send(core, job, None)
+    }
+}
+
+/// Runtime with a worker thread per core, each running their own reactor and
+/// executor.
+///
+/// Work is handed off by stashing it in the inbox of the worker and posting a
+/// message onto its ring, which wakes it up if it's blocked waiting for
+/// completions.
+#[must_use]
+pub struct Runtime {
+    handle: Handle,
+    threads: Vec<thread::JoinHandle<Result<()>>>,
+}
+
+impl Runtime {
+    /// Number of registered file slots each worker has for receiving
+    /// descriptors.
+    const DESCRIPTOR_SLOTS: u32 = 64;
+
+    /// Get a handle for sending work onto the workers.
+    pub const fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Start the specified number of workers with the queue size for their
+    /// reactors.
+    ///
+    /// # Errors
+    ///
+    /// If setting up any of the rings fails, such as when the kernel doesn't
+    /// support passing messages between them.
+    pub fn new(cores: usize, entries: u32) -> Result<Self> {
+        let messenger = Messenger::new()?;
+        let mut workers = Vec::with_capacity(cores);
+        let mut threads = Vec::with_capacity(cores);
+
+        let mut failure = None;
+
+        for core in 0..cores {
+            match Self::start_worker(core, entries) {
+                Ok((worker, thread)) => {
+                    workers.push(worker);
+                    threads.push(thread);
+                }
+                Err(error) => {
+                    failure = Some(error);
+                    break;
+                }
+            }
+        }
+
+        let runtime = Self {
+            handle: Handle {
+                shared: Arc::new(Shared {
+                    messenger: Mutex::new(messenger),
+                    workers,
+                    sequence: AtomicU32::new(0),
+                }),
+            },
+            threads,
+        };
+
+        // dropping stops the workers that did manage to start
+        failure.map_or(Ok(runtime), Err)
+    }
+
+    /// Stop every worker after their current iteration and wait for them to
+    /// exit, dropping any tasks that haven't finished.
+    ///
+    /// # Errors
+    ///
+    /// If any of the workers failed.
+    pub fn shutdown(mut self) -> Result<()> {
+        self.stop()
+    }
+
+    fn start_worker(core: usize, entries: u32) -> Result<(Worker, thread::JoinHandle<Result<()>>)> {
+        let inbox = Inbox::default();
+        let failures = Arc::new(AtomicU64::new(0));
+        let (sender, receiver) = mpsc::channel();
+
+        let thread = thread::Builder::new()
+            .name(format!("uring-worker-{core}"))
+            .spawn({
+                let inbox = Arc::clone(&inbox);
+                let failures = Arc::clone(&failures);
+                move || work(entries, &inbox, &failures, &sender)
+            })?;
+
+        let ring = receiver
+            .recv()
+            .map_err(|_| Error::other("worker exited before starting"))??;
+
+        Ok((
+            Worker {
+                failures,
+                inbox,
+                ring,
+            },
+            thread,
+        ))
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        let mut result = Ok(());
+
+        for core in 0..self.threads.len() {
+            if let Err(error) = self.handle.send(core, Job::Stop, None) {
+                result = result.and(Err(error));
+            }
+        }
+
+        for thread in self.threads.drain(..) {
+            let output = thread
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+
+            result = result.and(output);
+        }
+
+        result
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        // errors can only be observed through an explicit shutdown
+        _ = self.stop();
+    }
+}
+
+/// Body of a worker thread.
+fn work(
+    entries: u32,
+    inbox: &Inbox,
+    failures: &AtomicU64,
+    ready: &mpsc::Sender<Result<OwnedFd>>,
+) -> Result<()> {
+    let setup = Reactor::new(entries).and_then(|mut reactor| {
+        reactor.require(opcode::MsgRingData::CODE)?;
+        reactor.require(opcode::FixedFdInstall::CODE)?;
+        reactor.register_sparse_files(Runtime::DESCRIPTOR_SLOTS)?;
+
+        // duplicated so that the ring outlives the thread for anyone still
+        // holding a handle to it
+        let ring = reactor.as_fd().try_clone_to_owned()?;
+        Ok((reactor, ring))
+    });
+
+    let reactor = match setup {
+        Ok((reactor, ring)) => {
+            _ = ready.send(Ok(ring));
+            Rc::new(RefCell::new(reactor))
+        }
+        Err(error) => {
+            _ = ready.send(Err(error));
+            return Ok(());
+        }
+    };
+
+    let executor = Executor::new(Rc::clone(&reactor))?;
+    executor.run_until(async {
+        loop {
+            let message = poll_fn(|context| reactor.borrow_mut().poll_message(context)).await;
+
+            let job = u32::try_from(message.user_data()).ok().and_then(|id| {
+                inbox
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .remove(&id)
+            });
+
+            match job {
+                Some(Job::Spawn(function)) => function(),
+                Some(Job::Descriptor(function)) => match receive_descriptor(&message).await {
+                    Ok(descriptor) => function(descriptor),
+                    // only this job is lost, the worker keeps serving the rest
+                    Err(_) => _ = failures.fetch_add(1, Ordering::Relaxed),
+                },
+                Some(Job::Stop) => return Ok(()),
+                // not something sent by the runtime
+                None => (),
+            }
+        }
+    })?
+}
+
+/// Install the descriptor sent along with a message, always freeing up the
+/// slot it arrived in.
+async fn receive_descriptor(message: &cqueue::Entry) -> Result<OwnedFd> {
+    let slot =
+        u32::try_from(message.result()).map_err(|_| Error::from_raw_os_error(-message.result()))?;
+
+    let descriptor = FixedFdInstall::new(slot).into_batch().submit().await;
+    let unregistered = reactor::current().borrow_mut().unregister_file(slot);
+
+    // a descriptor that got installed is closed when returning the error
+    unregistered.and(descriptor)
+}
+
+/// Whether entering the messenger ring failed in a way that goes away by
+/// draining completions and trying again.
+fn is_transient(error: &Error) -> bool {
+    error.kind() == ErrorKind::Interrupted
+        || matches!(error.raw_os_error(), Some(libc::EBUSY | libc::EAGAIN))
+}
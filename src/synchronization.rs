@@ -1,6 +1,10 @@
 //! Naive synchronization primitives implemented on top of asynchronous futex
 //! operations.
 //!
+//! On kernels without futex operations, waiters are queued up in-process
+//! instead, which keeps working across threads as long as every participant
+//! has its own reactor.
+//!
 //! The implementations are adapted from memory of example algorithms shown on
 //! the lecture slides of a concurrency course and might very possibly not be
 //! entirely correct.
@@ -12,10 +16,7 @@ use std::{
     sync::atomic::{AtomicU32, Ordering},
 };
 
-use crate::{
-    operation::{Batch as _, FutexWait, FutexWake, Oneshot as _},
-    reactor::Reactor,
-};
+use crate::{futex, reactor::Reactor};
 
 /// Minimal asynchronous semaphore implementation.
 #[must_use]
@@ -34,10 +35,7 @@ impl Semaphore {
         loop {
             let count = self.count.load(Ordering::SeqCst);
             if count == 0 {
-                FutexWait::new(&self.count, count)
-                    .into_batch()
-                    .build_submission(reactor)
-                    .await?;
+                futex::wait(reactor, &self.count, count).await?;
 
                 continue;
             }
@@ -57,10 +55,7 @@ impl Semaphore {
 
     pub async fn release(&self, reactor: &RefCell<Reactor>) -> Result<()> {
         if self.count.fetch_add(1, Ordering::SeqCst) == 0 {
-            FutexWake::new(&self.count, 1)
-                .into_batch()
-                .build_submission(reactor)
-                .await?;
+            futex::wake(reactor, &self.count, 1).await?;
         }
 
         Ok(())
@@ -98,19 +93,13 @@ impl<T> Mutex<T> {
                 return Ok(());
             }
 
-            FutexWait::new(&self.state, 1)
-                .into_batch()
-                .build_submission(reactor)
-                .await?;
+            futex::wait(reactor, &self.state, 1).await?;
         }
     }
 
     async fn unlock(&self, reactor: &RefCell<Reactor>) -> Result<()> {
         if self.state.swap(Self::STATE_UNLOCKED, Ordering::Release) == Self::STATE_LOCKED {
-            FutexWake::new(&self.state, 1)
-                .into_batch()
-                .build_submission(reactor)
-                .await?;
+            futex::wake(reactor, &self.state, 1).await?;
         }
 
         Ok(())
@@ -181,10 +170,7 @@ impl ConditionVariable {
 
         guard.inner.unlock(reactor).await?;
 
-        FutexWait::new(&self.futex, 1)
-            .into_batch()
-            .build_submission(reactor)
-            .await?;
+        futex::wait(reactor, &self.futex, 1).await?;
 
         guard.inner.lock(reactor).await?;
 
@@ -194,10 +180,7 @@ impl ConditionVariable {
     pub async fn notify_one(&self, reactor: &RefCell<Reactor>) -> Result<()> {
         self.futex.fetch_sub(1, Ordering::SeqCst);
 
-        FutexWake::new(&self.futex, 1)
-            .into_batch()
-            .build_submission(reactor)
-            .await?;
+        futex::wake(reactor, &self.futex, 1).await?;
 
         Ok(())
     }
@@ -208,10 +191,7 @@ impl ConditionVariable {
             return Ok(());
         }
 
-        FutexWake::new(&self.futex, waiters)
-            .into_batch()
-            .build_submission(reactor)
-            .await?;
+        futex::wake(reactor, &self.futex, waiters).await?;
 
         Ok(())
     }